@@ -0,0 +1,149 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+
+use crate::file::write_atomic;
+
+/// Number of timestamped backups kept for each file
+pub const BACKUP_COUNT: usize = 10;
+
+/// Minimum time between two timestamped backups of the same file
+pub const BACKUP_INTERVAL: TimeDelta = TimeDelta::minutes(10);
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Path of the backup of `path` with the given `label`, e.g. `heroes.yaml.v0.bak`
+pub fn backup_path(path: &Path, label: &str) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{file_name}.{label}.bak"))
+}
+
+/// Copy the file at `path` to a backup next to it, returning the path of the backup
+pub fn create_backup(path: &Path, label: &str) -> io::Result<PathBuf> {
+    let backup = backup_path(path, label);
+    std::fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+/// Copy the file at `path` to a backup labelled with the current time. A counter is added to the
+/// label if a backup was already taken in the same second, e.g. `heroes.yaml.20250101-120000-2.bak`.
+pub fn create_timestamped_backup(path: &Path) -> io::Result<PathBuf> {
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let mut label = timestamp.clone();
+    let mut count = 1;
+    while backup_path(path, &label).exists() {
+        count += 1;
+        label = format!("{timestamp}-{count}");
+    }
+    create_backup(path, &label)
+}
+
+/// Time and counter of a timestamped backup label
+fn parse_label(label: &str) -> Option<(NaiveDateTime, u32)> {
+    if let Ok(timestamp) = NaiveDateTime::parse_from_str(label, TIMESTAMP_FORMAT) {
+        return Some((timestamp, 1));
+    }
+    let (timestamp, count) = label.rsplit_once('-')?;
+    Some((
+        NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?,
+        count.parse().ok()?,
+    ))
+}
+
+/// Timestamped backups of `path` with their time and counter, newest first
+fn timestamped_backups(path: &Path) -> Vec<((NaiveDateTime, u32), PathBuf)> {
+    let Some(file_name) = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+    else {
+        return Vec::new();
+    };
+    let mut backups: Vec<((NaiveDateTime, u32), PathBuf)> = list_backups(path)
+        .into_iter()
+        .filter_map(|backup| {
+            let name = backup.file_name()?.to_string_lossy().into_owned();
+            let label = name
+                .strip_prefix(&format!("{file_name}."))?
+                .strip_suffix(".bak")?;
+            Some((parse_label(label)?, backup))
+        })
+        .collect();
+    backups.sort_by(|(a, _), (b, _)| b.cmp(a));
+    backups
+}
+
+/// Back up `path` unless a timestamped backup was taken in the last [`BACKUP_INTERVAL`], then delete
+/// all but the newest [`BACKUP_COUNT`] timestamped backups. Returns the new backup, if one was taken.
+pub fn rotate_backups(path: &Path) -> io::Result<Option<PathBuf>> {
+    let now = Local::now().naive_local();
+    let due = timestamped_backups(path)
+        .first()
+        .is_none_or(|((newest, _), _)| now - *newest >= BACKUP_INTERVAL);
+    if !due {
+        return Ok(None);
+    }
+
+    backup_and_prune(path).map(Some)
+}
+
+/// Take a timestamped backup of `path`, then delete all but the newest [`BACKUP_COUNT`] of them
+fn backup_and_prune(path: &Path) -> io::Result<PathBuf> {
+    let backup = create_timestamped_backup(path)?;
+    for (_, old) in timestamped_backups(path).iter().skip(BACKUP_COUNT) {
+        std::fs::remove_file(old)?;
+    }
+    Ok(backup)
+}
+
+/// Every backup of `path`, most recently modified first
+pub fn list_backups(path: &Path) -> Vec<PathBuf> {
+    let Some(file_name) = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+    else {
+        return Vec::new();
+    };
+    let prefix = format!("{file_name}.");
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with(&prefix) && name.ends_with(".bak")
+        })
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path.with_file_name(entry.file_name()))
+        })
+        .collect();
+    backups.sort_by(|(a, _), (b, _)| b.cmp(a));
+    backups.into_iter().map(|(_, backup)| backup).collect()
+}
+
+/// Replace the file at `path` with `backup`. The replaced file is itself kept as a timestamped backup,
+/// pruned along with the others.
+pub fn restore_backup(path: &Path, backup: &Path) -> io::Result<()> {
+    // Read the backup first, as pruning may delete it if it is the oldest one
+    let contents = std::fs::read(backup)?;
+    if path.exists() {
+        backup_and_prune(path)?;
+    }
+    write_atomic(path, &contents)
+}
@@ -0,0 +1,475 @@
+use clap::{Args, Parser, Subcommand};
+use pickme::*;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Pick Overwatch 2 heroes from the command line
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Heroes file to read and update
+    #[arg(short, long, global = true, default_value = DEFAULT_FILE_PATH)]
+    file: PathBuf,
+    /// Roster of every hero, instead of the `roster.yaml` next to the heroes file or the built-in roster
+    #[arg(long, global = true)]
+    roster: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Pick a random hero matching the filters
+    Pick {
+        #[command(flatten)]
+        filters: FilterArgs,
+        /// Don't record the pick in the history file
+        #[arg(long)]
+        no_history: bool,
+        /// Seed for the random pick, so it can be reproduced
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Increase the level of a hero
+    LevelUp {
+        hero: String,
+        /// Also increase the level in this game mode: quick-play, competitive, stadium or arcade
+        #[arg(short, long)]
+        mode: Option<GameMode>,
+    },
+    /// Decrease the level of a hero
+    LevelDown {
+        hero: String,
+        /// Decrease the level in this game mode instead of the overall level
+        #[arg(short, long)]
+        mode: Option<GameMode>,
+    },
+    /// Set the level of a hero
+    SetLevel {
+        hero: String,
+        level: u32,
+        /// Set the level in this game mode instead of the overall level
+        #[arg(short, long)]
+        mode: Option<GameMode>,
+    },
+    /// Reset every hero to level 1
+    ResetLevels,
+    /// Toggle whether a hero is a favourite
+    Favourite { hero: String },
+    /// Toggle whether a hero is blacklisted
+    Blacklist { hero: String },
+    /// Rename a hero
+    Rename { hero: String, new_name: String },
+    /// Move a hero to another role
+    Move { hero: String, role: Role },
+    /// Delete a hero and its progress
+    Remove { hero: String },
+    /// Add a tag to a hero, such as "dive" or "learning"
+    Tag { hero: String, tag: String },
+    /// Remove a tag from a hero
+    Untag { hero: String, tag: String },
+    /// List heroes, highlighting the ones matching the filters
+    List(FilterArgs),
+    /// Show the most recent picks
+    History {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Record the outcome of the most recent pick: win, loss or draw
+    Outcome { outcome: Outcome },
+    /// Update heroes from a CSV file with name, role, level, favourite, blacklisted and stadium columns
+    Import { csv: PathBuf },
+    /// Write every hero to a CSV file
+    Export { csv: PathBuf },
+    /// Give every player a distinct hero for a role queue composition
+    Team {
+        /// Player and their heroes file as NAME=PATH, can be repeated
+        #[arg(short, long = "player", value_parser = parse_player, required = true)]
+        players: Vec<(String, PathBuf)>,
+        /// Role composition: 5v5, 6v6 or tanks-damages-supports such as 1-2-2
+        #[arg(short, long, default_value = "5v5")]
+        composition: Composition,
+        #[command(flatten)]
+        filters: FilterArgs,
+        /// Seed for the random team, so it can be reproduced
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+/// Random number generator seeded with `seed`, or from entropy if there is none
+fn rng(seed: Option<u64>) -> StdRng {
+    seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+}
+
+/// Parse a `NAME=PATH` player argument, using the file name as the player name if there is no `=`
+fn parse_player(arg: &str) -> Result<(String, PathBuf), String> {
+    match arg.split_once('=') {
+        Some((name, path)) => Ok((name.to_string(), PathBuf::from(path))),
+        None => {
+            let path = PathBuf::from(arg);
+            let name = path
+                .file_stem()
+                .ok_or(format!("invalid player '{arg}'"))?
+                .to_string_lossy()
+                .into_owned();
+            Ok((name, path))
+        }
+    }
+}
+
+#[derive(Args)]
+struct FilterArgs {
+    /// Only include heroes of this role, can be repeated. Defaults to every role
+    #[arg(short, long)]
+    role: Vec<Role>,
+    /// Only include heroes with this sub-role, such as hitscan or main-tank, can be repeated
+    #[arg(short, long)]
+    sub_role: Vec<SubRole>,
+    /// Only include heroes with this tag, can be repeated to require several tags
+    #[arg(short, long)]
+    tag: Vec<String>,
+    /// Leave out heroes with this tag, can be repeated
+    #[arg(long)]
+    exclude_tag: Vec<String>,
+    /// Only include favourite heroes
+    #[arg(long)]
+    favourite: bool,
+    /// Only include heroes with the lowest level
+    #[arg(long)]
+    lowest: bool,
+    /// Only include heroes available in stadium
+    #[arg(long)]
+    stadium: bool,
+    /// Make lower level heroes more likely to be picked
+    #[arg(long)]
+    weighted: bool,
+    /// How quickly the weight falls off with level: inverse, inverse-square or exponential
+    #[arg(long, default_value = "inverse")]
+    curve: WeightCurve,
+    /// Multiplier applied to the weight of favourite heroes
+    #[arg(long, default_value_t = 1.0)]
+    favourite_weight: f64,
+    /// Use levels in this game mode instead of overall levels: quick-play, competitive, stadium or arcade
+    #[arg(short, long)]
+    mode: Option<GameMode>,
+    /// Filter expression such as "role:tank or (favourite and level<5)", replacing the
+    /// role, sub-role, tag, favourite and stadium flags
+    #[arg(short, long)]
+    expr: Option<Expr>,
+}
+
+impl FilterArgs {
+    fn filters(&self) -> Filters {
+        let all_roles = self.role.is_empty();
+        Filters {
+            tank: all_roles || self.role.contains(&Role::Tank),
+            damage: all_roles || self.role.contains(&Role::Damage),
+            support: all_roles || self.role.contains(&Role::Support),
+            favourite: self.favourite,
+            lowest: self.lowest,
+            stadium: self.stadium,
+            weighted: self.weighted,
+            weights: Weights {
+                curve: self.curve,
+                favourite_multiplier: self.favourite_weight,
+            },
+            mode: self.mode,
+            sub_roles: self.sub_role.iter().copied().collect(),
+            include_tags: self
+                .tag
+                .iter()
+                .map(|tag| Hero::normalize_tag(tag))
+                .collect(),
+            exclude_tags: self
+                .exclude_tag
+                .iter()
+                .map(|tag| Hero::normalize_tag(tag))
+                .collect(),
+            expression: self.expr.clone(),
+            ..Filters::default()
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let roster = match &cli.roster {
+        Some(path) => match Roster::load(path) {
+            Ok(roster) => Some(roster),
+            Err(err) => {
+                eprintln!("Unable to load roster from '{}': {err}", path.display());
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    let load = |path: &Path| match &roster {
+        Some(roster) => load_heroes_with_roster(path, roster),
+        None => load_heroes(path),
+    };
+    let mut heroes = match load(&cli.file) {
+        Ok(heroes) => heroes,
+        Err(err) => {
+            eprintln!("Unable to load heroes from '{}': {err}", cli.file.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match cli.command {
+        Command::Pick {
+            filters,
+            no_history,
+            seed,
+        } => {
+            let filters = filters.filters();
+            match Picker::new().pick_with(&heroes, &filters, &mut rng(seed)) {
+                Pick::Hero(hero) => {
+                    println!("{}", hero.name);
+                    if no_history {
+                        return ExitCode::SUCCESS;
+                    }
+                    update_history(&cli.file, |history| {
+                        history.record(&hero, &filters);
+                        Ok(())
+                    })
+                }
+                Pick::Empty => {
+                    eprintln!("{}", Pick::Empty);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::LevelUp { hero, mode } => {
+            update_hero(&cli.file, &mut heroes, &hero, |hero| hero.level_up_in(mode))
+        }
+        Command::LevelDown { hero, mode } => update_hero(&cli.file, &mut heroes, &hero, |hero| {
+            hero.level_down_in(mode)
+        }),
+        Command::SetLevel { hero, level, mode } => {
+            update_hero(&cli.file, &mut heroes, &hero, |hero| {
+                hero.set_level_in(mode, level)
+            })
+        }
+        Command::ResetLevels => {
+            heroes.reset_levels();
+            if let Err(err) = save_heroes(&cli.file, &heroes) {
+                eprintln!("Unable to save heroes to '{}': {err}", cli.file.display());
+                return ExitCode::FAILURE;
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Favourite { hero } => {
+            update_hero(&cli.file, &mut heroes, &hero, Hero::toggle_favourite)
+        }
+        Command::Blacklist { hero } => {
+            update_hero(&cli.file, &mut heroes, &hero, Hero::toggle_blacklisted)
+        }
+        Command::Rename { hero, new_name } => edit_heroes(&cli.file, &mut heroes, |heroes| {
+            heroes.rename_hero(&hero, &new_name)
+        }),
+        Command::Move { hero, role } => edit_heroes(&cli.file, &mut heroes, |heroes| {
+            heroes.move_hero(&hero, role)
+        }),
+        Command::Remove { hero } => edit_heroes(&cli.file, &mut heroes, |heroes| {
+            heroes
+                .remove(&hero)
+                .map(|_| ())
+                .ok_or(EditError::NotFound(hero.clone()))
+        }),
+        Command::Tag { hero, tag } => update_hero(&cli.file, &mut heroes, &hero, |hero| {
+            hero.add_tag(&tag);
+        }),
+        Command::Untag { hero, tag } => update_hero(&cli.file, &mut heroes, &hero, |hero| {
+            hero.remove_tag(&tag);
+        }),
+        Command::List(args) => {
+            let filters = args.filters();
+            let picker = Picker::new();
+            let lowest_level = picker.lowest_level(&heroes, &filters);
+            for role in Role::ALL {
+                println!("{role}:");
+                for hero in heroes.role(role) {
+                    let marker = if picker.is_selected(hero, &filters, lowest_level) {
+                        '>'
+                    } else {
+                        ' '
+                    };
+                    let favourite = if hero.favourite { " ★" } else { "" };
+                    let blacklisted = if hero.blacklisted {
+                        " (blacklisted)"
+                    } else {
+                        ""
+                    };
+                    let tags: String = hero.tags.iter().map(|tag| format!(" #{tag}")).collect();
+                    println!(
+                        "{marker} {} - {}{favourite}{blacklisted}{tags}",
+                        filters.level(hero),
+                        hero.name
+                    );
+                }
+            }
+            ExitCode::SUCCESS
+        }
+        Command::History { count } => {
+            let Some(history) = load_history(&History::path_for(&cli.file)) else {
+                return ExitCode::FAILURE;
+            };
+            let skip = history.entries.len().saturating_sub(count);
+            for entry in history.entries.iter().skip(skip) {
+                println!("{entry}");
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Import { csv } => {
+            let count = match import_csv(&csv, &mut heroes) {
+                Ok(count) => count,
+                Err(err) => {
+                    eprintln!("Unable to import '{}': {err}", csv.display());
+                    return ExitCode::FAILURE;
+                }
+            };
+            if let Err(err) = save_heroes(&cli.file, &heroes) {
+                eprintln!("Unable to save heroes to '{}': {err}", cli.file.display());
+                return ExitCode::FAILURE;
+            }
+            println!("Imported {count} heroes");
+            ExitCode::SUCCESS
+        }
+        Command::Export { csv } => match export_csv(&csv, &heroes) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("Unable to export to '{}': {err}", csv.display());
+                ExitCode::FAILURE
+            }
+        },
+        Command::Team {
+            players,
+            composition,
+            filters,
+            seed,
+        } => {
+            let filters = filters.filters();
+            let players: Result<Vec<Player>, String> = players
+                .into_iter()
+                .map(|(name, path)| {
+                    let heroes = load(&path).map_err(|err| {
+                        format!(
+                            "Unable to load heroes for {name} from '{}': {err}",
+                            path.display()
+                        )
+                    })?;
+                    Ok(Player {
+                        name,
+                        heroes,
+                        filters: filters.clone(),
+                    })
+                })
+                .collect();
+            let players = match players {
+                Ok(players) => players,
+                Err(err) => {
+                    eprintln!("{err}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            match generate_team_with(&players, composition, &mut rng(seed)) {
+                Ok(assignments) => {
+                    for assignment in assignments {
+                        println!("{assignment}");
+                    }
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("{err}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Outcome { outcome } => update_history(&cli.file, |history| {
+            let entry = history.last_mut().ok_or("No picks in the history")?;
+            entry.outcome = Some(outcome);
+            println!("{entry}");
+            Ok(())
+        }),
+    }
+}
+
+/// Apply `update` to the hero called `name` and save the heroes file
+fn update_hero(
+    path: &Path,
+    heroes: &mut Heroes,
+    name: &str,
+    update: impl FnOnce(&mut Hero),
+) -> ExitCode {
+    let Some(hero) = heroes.find_mut(name) else {
+        eprintln!("No hero named '{name}'");
+        return ExitCode::FAILURE;
+    };
+    update(hero);
+    println!("{hero}");
+
+    if let Err(err) = save_heroes(path, heroes) {
+        eprintln!("Unable to save heroes to '{}': {err}", path.display());
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Apply `edit` to the heroes and save the heroes file
+fn edit_heroes(
+    path: &Path,
+    heroes: &mut Heroes,
+    edit: impl FnOnce(&mut Heroes) -> Result<(), EditError>,
+) -> ExitCode {
+    if let Err(err) = edit(heroes) {
+        eprintln!("{err}");
+        return ExitCode::FAILURE;
+    }
+
+    if let Err(err) = save_heroes(path, heroes) {
+        eprintln!("Unable to save heroes to '{}': {err}", path.display());
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Load the history file at `path`, printing why if it couldn't be loaded
+fn load_history(path: &Path) -> Option<History> {
+    match History::load(path) {
+        Ok(history) => Some(history),
+        Err(err) => {
+            eprintln!("Unable to load history from '{}': {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Apply `update` to the history kept next to the heroes file at `path` and save it
+fn update_history(
+    path: &Path,
+    update: impl FnOnce(&mut History) -> Result<(), &'static str>,
+) -> ExitCode {
+    let history_path = History::path_for(path);
+    // Never write over a history file that couldn't be read, so its entries aren't lost
+    let Some(mut history) = load_history(&history_path) else {
+        return ExitCode::FAILURE;
+    };
+    if let Err(err) = update(&mut history) {
+        eprintln!("{err}");
+        return ExitCode::FAILURE;
+    }
+
+    if let Err(err) = history.save(&history_path) {
+        eprintln!(
+            "Unable to save history to '{}': {err}",
+            history_path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,652 @@
+//! Boolean filter expressions such as `role:tank or (favourite and level<5) and not tag:learning`.
+//!
+//! Terms:
+//! - `role:<role>`, `subrole:<sub-role>`, `tag:<tag>` and `name:<name>`, quoting values with spaces
+//! - `favourite` and `stadium`
+//! - `level` compared to a number with `<`, `<=`, `>`, `>=`, `=` or `!=`
+//! - `true` and `false`
+//!
+//! Terms are combined with `not`, `and` and `or`, in that order of precedence, and parentheses.
+
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{GameMode, Hero, Role, SubRole};
+
+/// Comparison between a hero's level and a number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    fn compare(&self, left: u32, right: u32) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Less => write!(f, "<"),
+            Comparison::LessOrEqual => write!(f, "<="),
+            Comparison::Greater => write!(f, ">"),
+            Comparison::GreaterOrEqual => write!(f, ">="),
+            Comparison::Equal => write!(f, "="),
+            Comparison::NotEqual => write!(f, "!="),
+        }
+    }
+}
+
+/// Parsed filter expression, see the [module documentation](self) for the syntax
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Bool(bool),
+    Role(Role),
+    SubRole(SubRole),
+    Tag(String),
+    /// Hero name, matched ignoring case
+    Name(String),
+    Favourite,
+    Stadium,
+    /// Level in the selected game mode compared to a number
+    Level(Comparison, u32),
+    Not(Box<Expr>),
+    /// Chain of `and`s, kept flat so long chains don't nest deeply
+    And(Vec<Expr>),
+    /// Chain of `or`s, kept flat so long chains don't nest deeply
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Whether `hero` matches the expression, using its level in `mode`
+    pub fn matches(&self, hero: &Hero, mode: Option<GameMode>) -> bool {
+        match self {
+            Expr::Bool(value) => *value,
+            Expr::Role(role) => hero.role == *role,
+            Expr::SubRole(sub_role) => hero.sub_roles.contains(sub_role),
+            Expr::Tag(tag) => hero.tags.contains(tag),
+            Expr::Name(name) => hero.name.to_lowercase() == name.to_lowercase(),
+            Expr::Favourite => hero.favourite,
+            Expr::Stadium => hero.stadium,
+            Expr::Level(comparison, level) => comparison.compare(hero.level_in(mode), *level),
+            Expr::Not(expr) => !expr.matches(hero, mode),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.matches(hero, mode)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.matches(hero, mode)),
+        }
+    }
+
+    /// Every expression in `exprs` combined with `and`, or `true` if there are none
+    pub fn all(exprs: impl IntoIterator<Item = Expr>) -> Self {
+        let mut exprs: Vec<Expr> = exprs.into_iter().collect();
+        match exprs.len() {
+            0 => Expr::Bool(true),
+            1 => exprs.remove(0),
+            _ => Expr::And(exprs),
+        }
+    }
+
+    /// Every expression in `exprs` combined with `or`, or `false` if there are none
+    pub fn any(exprs: impl IntoIterator<Item = Expr>) -> Self {
+        let mut exprs: Vec<Expr> = exprs.into_iter().collect();
+        match exprs.len() {
+            0 => Expr::Bool(false),
+            1 => exprs.remove(0),
+            _ => Expr::Or(exprs),
+        }
+    }
+
+    /// How tightly the expression binds, to decide where parentheses are needed
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(..) => 1,
+            Expr::And(..) => 2,
+            Expr::Not(_) => 3,
+            _ => 4,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>, precedence: u8) -> std::fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+
+    /// Write `exprs` separated by `operator`. Operands binding no tighter than the chain itself
+    /// get parentheses, so a chain nested in another reads back the same.
+    fn fmt_chain(
+        f: &mut std::fmt::Formatter<'_>,
+        exprs: &[Expr],
+        operator: &str,
+        precedence: u8,
+    ) -> std::fmt::Result {
+        for (index, expr) in exprs.iter().enumerate() {
+            if index > 0 {
+                write!(f, " {operator} ")?;
+            }
+            expr.fmt_operand(f, precedence + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `value`, quoting it if it wouldn't be read back as a single word. Quotes and backslashes in
+/// quoted values are escaped with a backslash.
+fn fmt_value(f: &mut std::fmt::Formatter<'_>, value: &str) -> std::fmt::Result {
+    if !value.is_empty() && value.chars().all(is_word_char) {
+        write!(f, "{value}")
+    } else {
+        let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+        write!(f, "\"{escaped}\"")
+    }
+}
+
+impl std::ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Bool(value) => write!(f, "{value}"),
+            Expr::Role(role) => write!(f, "role:{}", role.to_string().to_lowercase()),
+            Expr::SubRole(sub_role) => {
+                let name: String = sub_role.to_string().to_lowercase().replace(' ', "-");
+                write!(f, "subrole:{name}")
+            }
+            Expr::Tag(tag) => {
+                write!(f, "tag:")?;
+                fmt_value(f, tag)
+            }
+            Expr::Name(name) => {
+                write!(f, "name:")?;
+                fmt_value(f, name)
+            }
+            Expr::Favourite => write!(f, "favourite"),
+            Expr::Stadium => write!(f, "stadium"),
+            Expr::Level(comparison, level) => write!(f, "level{comparison}{level}"),
+            Expr::Not(expr) => {
+                write!(f, "not ")?;
+                expr.fmt_operand(f, 3)
+            }
+            Expr::And(exprs) => Expr::fmt_chain(f, exprs, "and", 2),
+            Expr::Or(exprs) => Expr::fmt_chain(f, exprs, "or", 1),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            index: 0,
+            end: s.chars().count(),
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some((token, position)) => Err(ParseError::new(
+                position,
+                format!("expected 'and' or 'or', found {token}"),
+            )),
+        }
+    }
+}
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Error parsing an [`Expr`]
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Zero-based character position of the error
+    pub position: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new<S: Into<String>>(position: usize, message: S) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Colon,
+    Comparison(Comparison),
+    Open,
+    Close,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{word}'"),
+            Token::Quoted(value) => write!(f, "\"{value}\""),
+            Token::Colon => write!(f, "':'"),
+            Token::Comparison(comparison) => write!(f, "'{comparison}'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '\'')
+}
+
+/// Split `s` into tokens, each with its character position
+fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().enumerate().peekable();
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ':' => Token::Colon,
+            '<' | '>' | '=' | '!' => {
+                let equals = chars.next_if(|(_, next)| *next == '=').is_some();
+                match (c, equals) {
+                    ('<', false) => Token::Comparison(Comparison::Less),
+                    ('<', true) => Token::Comparison(Comparison::LessOrEqual),
+                    ('>', false) => Token::Comparison(Comparison::Greater),
+                    ('>', true) => Token::Comparison(Comparison::GreaterOrEqual),
+                    ('=', _) => Token::Comparison(Comparison::Equal),
+                    ('!', true) => Token::Comparison(Comparison::NotEqual),
+                    _ => return Err(ParseError::new(position, "expected '!='")),
+                }
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c @ ('"' | '\\'))) => value.push(c),
+                            Some((position, _)) => {
+                                return Err(ParseError::new(
+                                    position - 1,
+                                    "unknown escape, only \\\" and \\\\ are allowed",
+                                ))
+                            }
+                            None => return Err(ParseError::new(position, "unterminated quote")),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ParseError::new(position, "unterminated quote")),
+                    }
+                }
+                Token::Quoted(value)
+            }
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            c => return Err(ParseError::new(position, format!("unexpected '{c}'"))),
+        };
+        tokens.push((token, position));
+    }
+    Ok(tokens)
+}
+
+/// Deepest nesting of parentheses and `not`s the parser accepts
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser over the tokens of an expression
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    /// Position reported for errors at the end of the expression
+    end: usize,
+    /// Number of parentheses and `not`s the parser is inside
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(&Token, usize)> {
+        self.tokens
+            .get(self.index)
+            .map(|(token, position)| (token, *position))
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), ParseError> {
+        let token = self
+            .tokens
+            .get(self.index)
+            .cloned()
+            .ok_or_else(|| ParseError::new(self.end, "unexpected end of expression"))?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    /// Consume the next token if it is the keyword `keyword`
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some((Token::Word(word), _)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut exprs = vec![self.and()?];
+        while self.keyword("or") {
+            exprs.push(self.and()?);
+        }
+        Ok(Expr::any(exprs))
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut exprs = vec![self.not()?];
+        while self.keyword("and") {
+            exprs.push(self.not()?);
+        }
+        Ok(Expr::all(exprs))
+    }
+
+    /// Run `parse` one level deeper, failing instead of overflowing the stack on deeply nested input
+    fn nested(
+        &mut self,
+        position: usize,
+        parse: impl FnOnce(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(ParseError::new(position, "expression is nested too deeply"));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn not(&mut self) -> Result<Expr, ParseError> {
+        let position = self.peek().map_or(self.end, |(_, position)| position);
+        if self.keyword("not") {
+            Ok(!self.nested(position, Self::not)?)
+        } else {
+            self.term()
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let (token, position) = self.next()?;
+        let word = match token {
+            Token::Open => {
+                let expr = self.nested(position, Self::or)?;
+                return match self.next()? {
+                    (Token::Close, _) => Ok(expr),
+                    (token, position) => Err(ParseError::new(
+                        position,
+                        format!("expected ')', found {token}"),
+                    )),
+                };
+            }
+            Token::Word(word) => word.to_lowercase(),
+            token => {
+                return Err(ParseError::new(
+                    position,
+                    format!("expected a filter, found {token}"),
+                ))
+            }
+        };
+
+        match word.as_str() {
+            "true" => Ok(Expr::Bool(true)),
+            "false" => Ok(Expr::Bool(false)),
+            "favourite" | "favorite" => Ok(Expr::Favourite),
+            "stadium" => Ok(Expr::Stadium),
+            "level" => {
+                let comparison = match self.next()? {
+                    (Token::Comparison(comparison), _) => comparison,
+                    (token, position) => {
+                        return Err(ParseError::new(
+                            position,
+                            format!("expected a comparison such as '<', found {token}"),
+                        ))
+                    }
+                };
+                let (value, position) = self.value()?;
+                let level = value
+                    .parse()
+                    .map_err(|_| ParseError::new(position, format!("invalid level '{value}'")))?;
+                Ok(Expr::Level(comparison, level))
+            }
+            "role" | "subrole" | "sub-role" | "sub_role" | "tag" | "name" => {
+                match self.next()? {
+                    (Token::Colon, _) => {}
+                    (token, position) => {
+                        return Err(ParseError::new(
+                            position,
+                            format!("expected ':' after '{word}', found {token}"),
+                        ))
+                    }
+                }
+                let (value, position) = self.value()?;
+                match word.as_str() {
+                    "role" => value
+                        .parse()
+                        .map(Expr::Role)
+                        .map_err(|message| ParseError::new(position, message)),
+                    "tag" => Ok(Expr::Tag(Hero::normalize_tag(&value))),
+                    "name" => Ok(Expr::Name(value)),
+                    _ => value
+                        .parse()
+                        .map(Expr::SubRole)
+                        .map_err(|message| ParseError::new(position, message)),
+                }
+            }
+            _ => Err(ParseError::new(
+                position,
+                format!("unknown filter '{word}'"),
+            )),
+        }
+    }
+
+    /// A word or quoted value
+    fn value(&mut self) -> Result<(String, usize), ParseError> {
+        match self.next()? {
+            (Token::Word(value) | Token::Quoted(value), position) => Ok((value, position)),
+            (token, position) => Err(ParseError::new(
+                position,
+                format!("expected a value, found {token}"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Expr {
+        s.parse()
+            .unwrap_or_else(|err| panic!("'{s}' didn't parse: {err}"))
+    }
+
+    fn error(s: &str) -> ParseError {
+        s.parse::<Expr>().expect_err(s)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            parse("role:tank or favourite and not stadium"),
+            Expr::any([
+                Expr::Role(Role::Tank),
+                Expr::all([Expr::Favourite, !Expr::Stadium]),
+            ])
+        );
+        assert_eq!(
+            parse("(role:tank or favourite) and stadium"),
+            Expr::all([
+                Expr::any([Expr::Role(Role::Tank), Expr::Favourite]),
+                Expr::Stadium,
+            ])
+        );
+        assert_eq!(
+            parse("NOT level>=3 AND tag:Fun"),
+            Expr::all([
+                !Expr::Level(Comparison::GreaterOrEqual, 3),
+                Expr::Tag("fun".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn terms() {
+        assert_eq!(parse("subrole:main-tank"), Expr::SubRole(SubRole::MainTank));
+        assert_eq!(
+            parse("sub_role:\"Flex Support\""),
+            Expr::SubRole(SubRole::FlexSupport)
+        );
+        assert_eq!(
+            parse("name:\"Soldier: 76\""),
+            Expr::Name("Soldier: 76".to_string())
+        );
+        assert_eq!(parse("level!=1"), Expr::Level(Comparison::NotEqual, 1));
+        assert_eq!(
+            parse("favorite or false"),
+            Expr::any([Expr::Favourite, Expr::Bool(false)])
+        );
+    }
+
+    #[test]
+    fn error_columns() {
+        assert_eq!(
+            error("role:tank and").to_string(),
+            "unexpected end of expression at column 14"
+        );
+        assert_eq!(
+            error("level<x").to_string(),
+            "invalid level 'x' at column 7"
+        );
+        assert_eq!(error("role:healer").position, 5);
+        assert_eq!(error("favourite stadium").position, 10);
+        assert_eq!(error("(favourite").position, 10);
+        assert_eq!(
+            error("tag:\"fun").to_string(),
+            "unterminated quote at column 5"
+        );
+        assert_eq!(error("tag:\"a\\b\"").position, 6);
+        assert_eq!(error("level=1 & stadium").position, 8);
+        assert_eq!(error("colour:red").position, 0);
+    }
+
+    #[test]
+    fn quoted_values_are_escaped() {
+        for tag in ["a\"b", "back\\slash", "two words", "\"", ""] {
+            let expr = Expr::Tag(tag.to_string());
+            let text = expr.to_string();
+            assert_eq!(parse(&text), expr, "{text}");
+        }
+        assert_eq!(Expr::Tag("a\"b".to_string()).to_string(), "tag:\"a\\\"b\"");
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in [
+            "role:tank or (favourite and level<5) and not tag:learning",
+            "not (role:damage or role:support) and subrole:main-tank",
+            "(true or false) and not not stadium",
+            "name:\"Soldier: 76\" or level>=10",
+        ] {
+            let expr = parse(text);
+            assert_eq!(parse(&expr.to_string()), expr, "{text}");
+        }
+        assert_eq!(
+            parse("((role:tank)) and (favourite or stadium)").to_string(),
+            "role:tank and (favourite or stadium)"
+        );
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let deep = format!("{}true{}", "(".repeat(20_000), ")".repeat(20_000));
+        assert_eq!(error(&deep).message, "expression is nested too deeply");
+        assert_eq!(
+            error(&"not ".repeat(20_000)).message,
+            "expression is nested too deeply"
+        );
+        let shallow = format!("{}true{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(parse(&shallow), Expr::Bool(true));
+    }
+
+    #[test]
+    fn long_chains_are_flat() {
+        let chain = vec!["favourite"; 200_000].join(" and ");
+        let expr = parse(&chain);
+        assert!(matches!(&expr, Expr::And(exprs) if exprs.len() == 200_000));
+        let mut hero = Hero::new("Ana", Role::Support, false);
+        assert!(!expr.matches(&hero, None));
+        hero.favourite = true;
+        assert!(expr.matches(&hero, None));
+        assert_eq!(expr.to_string(), chain);
+
+        let chain = vec!["stadium"; 200_000].join(" or ");
+        assert!(!parse(&chain).matches(&hero, None));
+    }
+
+    #[test]
+    fn nested_chains_keep_parentheses() {
+        let expr = parse("(favourite and stadium) and level<3");
+        assert_eq!(expr.to_string(), "(favourite and stadium) and level<3");
+        assert_eq!(parse(&expr.to_string()), expr);
+        assert_eq!(
+            parse("favourite and stadium and level<3"),
+            Expr::all([
+                Expr::Favourite,
+                Expr::Stadium,
+                Expr::Level(Comparison::Less, 3)
+            ])
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+
+use serde::de::DeserializeOwned;
+use serde_yaml::Value;
+
+use crate::{
+    backup::{create_backup, rotate_backups},
+    schema::{self, SchemaError},
+    Heroes, Roster,
+};
+
+pub const DEFAULT_FILE_PATH: &str = "heroes.yaml";
+
+/// Storage format of a heroes file, chosen by the file extension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Yaml, Format::Json, Format::Toml];
+
+    /// File extensions of the format, the first one being preferred
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Format::Yaml => &["yaml", "yml"],
+            Format::Json => &["json"],
+            Format::Toml => &["toml"],
+        }
+    }
+
+    /// Format of the file at `path`, YAML unless the extension belongs to another format
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+            .unwrap_or(Format::Yaml)
+    }
+
+    fn parse<T: DeserializeOwned>(&self, contents: &str) -> Result<T, LoadError> {
+        match self {
+            Format::Yaml => Ok(serde_yaml::from_str(contents)?),
+            Format::Json => serde_json::from_str(contents).map_err(|err| LoadError::Parse {
+                message: err.to_string(),
+                line: (err.line() > 0).then_some(err.line()),
+                column: (err.column() > 0).then_some(err.column()),
+            }),
+            Format::Toml => toml::from_str(contents).map_err(|err| {
+                let (line, column) = err
+                    .span()
+                    .map(|span| line_column(contents, span.start))
+                    .unzip();
+                LoadError::Parse {
+                    message: err.message().to_string(),
+                    line,
+                    column,
+                }
+            }),
+        }
+    }
+
+    /// Serialize `heroes` in this format
+    pub fn serialize(&self, heroes: &Heroes) -> Result<String, SaveError> {
+        match self {
+            Format::Yaml => {
+                serde_yaml::to_string(heroes).map_err(|err| SaveError::Serialize(err.into()))
+            }
+            Format::Json => {
+                serde_json::to_string_pretty(heroes).map_err(|err| SaveError::Serialize(err.into()))
+            }
+            Format::Toml => toml::to_string(heroes).map_err(|err| SaveError::Serialize(err.into())),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Yaml => write!(f, "YAML"),
+            Format::Json => write!(f, "JSON"),
+            Format::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+/// One-based line and column of the byte `offset` into `contents`
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+    (line, column)
+}
+
+/// Error loading a heroes file
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file exists but couldn't be read
+    Io(io::Error),
+    /// The file isn't valid YAML or doesn't match the heroes layout
+    Parse {
+        message: String,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+    /// The file can't be upgraded to the current schema version
+    Schema(SchemaError),
+    /// The file needed upgrading but couldn't be backed up first
+    Backup(io::Error),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "Could not read heroes file: {err}"),
+            LoadError::Parse { message, .. } => write!(f, "Could not parse heroes file: {message}"),
+            LoadError::Schema(err) => write!(f, "{err}"),
+            LoadError::Backup(err) => {
+                write!(
+                    f,
+                    "Could not back up heroes file before upgrading it: {err}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(err) | LoadError::Backup(err) => Some(err),
+            LoadError::Schema(err) => Some(err),
+            LoadError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for LoadError {
+    fn from(err: serde_yaml::Error) -> Self {
+        let location = err.location();
+        LoadError::Parse {
+            message: err.to_string(),
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+        }
+    }
+}
+
+impl From<SchemaError> for LoadError {
+    fn from(err: SchemaError) -> Self {
+        LoadError::Schema(err)
+    }
+}
+
+/// Load heroes from `path`, migrating them to the [`Roster::active`] roster for that path
+pub fn load_heroes(path: &Path) -> Result<Heroes, LoadError> {
+    load_heroes_with_roster(path, &Roster::active(path))
+}
+
+/// Load heroes from `path`, or the heroes of `roster` if the file doesn't exist yet
+pub fn load_heroes_with_roster(path: &Path, roster: &Roster) -> Result<Heroes, LoadError> {
+    if !path.exists() {
+        eprintln!("Heroes file does not exist, loading defaults");
+        return Ok(roster.heroes());
+    }
+    let contents = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+    let mut heroes = parse_heroes(path, &contents)?;
+    heroes.migrate(roster);
+    Ok(heroes)
+}
+
+/// Parse the heroes file at `path` with `contents`, upgrading it to the current schema version.
+///
+/// The file is backed up before any upgrade is applied.
+fn parse_heroes(path: &Path, contents: &str) -> Result<Heroes, LoadError> {
+    let format = Format::from_path(path);
+    let mut document: Value = format.parse(contents)?;
+    let version = schema::version(&document);
+    if version == schema::CURRENT_VERSION {
+        // Parse the text again rather than the document so errors keep their location
+        return format.parse(contents);
+    }
+
+    if version < schema::CURRENT_VERSION {
+        let backup = create_backup(path, &format!("v{version}")).map_err(LoadError::Backup)?;
+        eprintln!("Backed up heroes file to '{}'", backup.display());
+    }
+    schema::upgrade(&mut document)?;
+    Ok(serde_yaml::from_value(document)?)
+}
+
+/// Error saving a heroes file
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Serialize(Box<dyn Error + Send + Sync>),
+    /// The existing file couldn't be backed up before being replaced
+    Backup(io::Error),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "Could not write heroes file: {err}"),
+            SaveError::Serialize(err) => write!(f, "Could not serialize heroes: {err}"),
+            SaveError::Backup(err) => write!(f, "Could not back up heroes file: {err}"),
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Io(err) | SaveError::Backup(err) => Some(err),
+            SaveError::Serialize(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Write `heroes` to `path` in the [`Format`] for its extension.
+///
+/// The previous file is backed up with [`rotate_backups`] and replaced atomically, so a failed save
+/// never leaves a truncated file behind. Nothing is written if the file already has the same contents.
+pub fn save_heroes(path: &Path, heroes: &Heroes) -> Result<(), SaveError> {
+    let contents = Format::from_path(path).serialize(heroes)?;
+    if path.exists() {
+        if std::fs::read_to_string(path).is_ok_and(|current| current == contents) {
+            return Ok(());
+        }
+        if let Some(backup) = rotate_backups(path).map_err(SaveError::Backup)? {
+            eprintln!("Backed up heroes file to '{}'", backup.display());
+        }
+    }
+    write_atomic(path, contents.as_bytes()).map_err(SaveError::Io)
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it into place, creating any missing
+/// parent directories
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent_dir) = path.parent() {
+        std::fs::create_dir_all(parent_dir)?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.tmp"));
+    let mut temp_file = File::create(&temp_path)?;
+    temp_file.write_all(contents)?;
+    temp_file.sync_all()?;
+    std::fs::rename(&temp_path, path)
+}
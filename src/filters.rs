@@ -0,0 +1,319 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Expr, GameMode, Hero, Role, SubRole, Weights};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Filters {
+    pub tank: bool,
+    pub damage: bool,
+    pub support: bool,
+    pub favourite: bool,
+    pub lowest: bool,
+    #[serde(default = "Default::default")]
+    pub unique: bool,
+    #[serde(default)]
+    pub stadium: bool,
+    /// Pick heroes with a probability weighted by their level instead of uniformly
+    #[serde(default)]
+    pub weighted: bool,
+    #[serde(default)]
+    pub weights: Weights,
+    /// Game mode whose levels are used for the `lowest` filter, weights and levelling up, or the
+    /// overall level if there is none. The stadium mode also only includes heroes available in stadium.
+    #[serde(default)]
+    pub mode: Option<GameMode>,
+    /// Only include heroes with one of these sub-roles. Sub-roles only restrict heroes of their own
+    /// role, so heroes of a role without any selected sub-roles are all included.
+    #[serde(default)]
+    pub sub_roles: BTreeSet<SubRole>,
+    /// Only include heroes with every one of these tags
+    #[serde(default)]
+    pub include_tags: BTreeSet<String>,
+    /// Leave out heroes with any of these tags
+    #[serde(default)]
+    pub exclude_tags: BTreeSet<String>,
+    /// Expression used instead of the role, favourite, stadium, sub-role and tag filters
+    #[serde(default)]
+    pub expression: Option<Expr>,
+}
+
+impl Filters {
+    /// Whether `hero` passes the filters, ignoring the `lowest` filter which depends on the whole selection
+    pub fn is_selected(&self, hero: &Hero, session_picked: &[Hero]) -> bool {
+        let matches = match &self.expression {
+            Some(expression) => expression.matches(hero, self.mode),
+            None => self.matches_checkboxes(hero),
+        };
+        matches
+            && !(self.unique && session_picked.contains(hero))
+            && (self.mode != Some(GameMode::Stadium) || hero.stadium)
+            && !hero.blacklisted
+    }
+
+    /// Whether `hero` passes the filters that can be replaced by an expression
+    fn matches_checkboxes(&self, hero: &Hero) -> bool {
+        (hero.favourite || !self.favourite)
+            && ((self.tank && hero.role == Role::Tank)
+                || (self.support && hero.role == Role::Support)
+                || (self.damage && hero.role == Role::Damage))
+            && (if self.stadium { hero.stadium } else { true })
+            && self.has_sub_role(hero)
+            && self.include_tags.is_subset(&hero.tags)
+            && self.exclude_tags.is_disjoint(&hero.tags)
+    }
+
+    /// Expression selecting the same heroes as the role, favourite, stadium, sub-role and tag filters
+    pub fn to_expression(&self) -> Expr {
+        let mut terms = Vec::new();
+        let roles: Vec<Role> = Role::ALL
+            .into_iter()
+            .filter(|role| match role {
+                Role::Tank => self.tank,
+                Role::Damage => self.damage,
+                Role::Support => self.support,
+            })
+            .collect();
+        if roles.len() < Role::ALL.len() {
+            terms.push(Expr::any(roles.into_iter().map(Expr::Role)));
+        }
+        if self.favourite {
+            terms.push(Expr::Favourite);
+        }
+        if self.stadium {
+            terms.push(Expr::Stadium);
+        }
+        for role in Role::ALL {
+            let sub_roles: Vec<SubRole> = self
+                .sub_roles
+                .iter()
+                .copied()
+                .filter(|sub_role| sub_role.role() == role)
+                .collect();
+            if !sub_roles.is_empty() {
+                terms.push(Expr::any(
+                    std::iter::once(!Expr::Role(role))
+                        .chain(sub_roles.into_iter().map(Expr::SubRole)),
+                ));
+            }
+        }
+        terms.extend(self.include_tags.iter().cloned().map(Expr::Tag));
+        terms.extend(self.exclude_tags.iter().cloned().map(|tag| !Expr::Tag(tag)));
+        Expr::all(terms)
+    }
+
+    /// Whether `hero` has one of the selected sub-roles for its role, if there are any
+    fn has_sub_role(&self, hero: &Hero) -> bool {
+        let mut selected = self
+            .sub_roles
+            .iter()
+            .filter(|sub_role| sub_role.role() == hero.role)
+            .peekable();
+        selected.peek().is_none() || selected.any(|sub_role| hero.sub_roles.contains(sub_role))
+    }
+
+    /// Level of `hero` in the selected game mode
+    pub fn level(&self, hero: &Hero) -> u32 {
+        hero.level_in(self.mode)
+    }
+
+    /// Weight of `hero` for weighted picks, using its level in the selected game mode
+    pub fn weight(&self, hero: &Hero) -> f64 {
+        self.weights.weight(hero, self.level(hero))
+    }
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        Self {
+            tank: true,
+            damage: true,
+            support: true,
+            favourite: false,
+            lowest: false,
+            unique: false,
+            stadium: false,
+            weighted: false,
+            weights: Weights::default(),
+            mode: None,
+            sub_roles: BTreeSet::new(),
+            include_tags: BTreeSet::new(),
+            exclude_tags: BTreeSet::new(),
+            expression: None,
+        }
+    }
+}
+
+impl Display for Filters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(expression) = &self.expression {
+            return write!(f, "{expression}");
+        }
+        let mut enabled = Vec::new();
+        if let Some(mode) = self.mode {
+            enabled.push(mode.to_string());
+        }
+        for (flag, name) in [
+            (self.tank, "Tank"),
+            (self.damage, "Damage"),
+            (self.support, "Support"),
+            (self.favourite, "Favourite"),
+            (self.lowest, "Lowest"),
+            (self.unique, "Unique"),
+            (self.stadium, "Stadium"),
+        ] {
+            if flag {
+                enabled.push(name.to_string());
+            }
+        }
+        enabled.extend(self.sub_roles.iter().map(SubRole::to_string));
+        enabled.extend(self.include_tags.iter().map(|tag| format!("+{tag}")));
+        enabled.extend(self.exclude_tags.iter().map(|tag| format!("-{tag}")));
+        if self.weighted {
+            enabled.push(format!("Weighted ({})", self.weights.curve));
+        }
+        write!(f, "{}", enabled.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hero(role: Role, favourite: bool, stadium: bool, blacklisted: bool) -> Hero {
+        let mut hero = Hero::new(format!("{role} hero"), role, stadium);
+        hero.favourite = favourite;
+        hero.blacklisted = blacklisted;
+        hero
+    }
+
+    /// Every combination of `count` flags
+    fn combinations(count: u32) -> impl Iterator<Item = Vec<bool>> {
+        (0..1u32 << count).map(move |bits| (0..count).map(|bit| bits & (1 << bit) != 0).collect())
+    }
+
+    #[test]
+    fn is_selected_truth_table() {
+        for flags in combinations(6) {
+            let filters = Filters {
+                tank: flags[0],
+                damage: flags[1],
+                support: flags[2],
+                favourite: flags[3],
+                stadium: flags[4],
+                unique: flags[5],
+                ..Filters::default()
+            };
+            for hero_flags in combinations(4) {
+                for role in Role::ALL {
+                    let hero = hero(role, hero_flags[0], hero_flags[1], hero_flags[2]);
+                    let picked = hero_flags[3];
+                    let session = if picked { vec![hero.clone()] } else { vec![] };
+
+                    let role_selected = match role {
+                        Role::Tank => filters.tank,
+                        Role::Damage => filters.damage,
+                        Role::Support => filters.support,
+                    };
+                    let expected = role_selected
+                        && (!filters.favourite || hero.favourite)
+                        && (!filters.stadium || hero.stadium)
+                        && !(filters.unique && picked)
+                        && !hero.blacklisted;
+                    assert_eq!(
+                        filters.is_selected(&hero, &session),
+                        expected,
+                        "{filters:?} with {hero:?}, picked: {picked}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stadium_mode_requires_stadium_heroes() {
+        let filters = Filters {
+            mode: Some(GameMode::Stadium),
+            ..Filters::default()
+        };
+        assert!(filters.is_selected(&hero(Role::Tank, false, true, false), &[]));
+        assert!(!filters.is_selected(&hero(Role::Tank, false, false, false), &[]));
+    }
+
+    #[test]
+    fn sub_roles_only_filter_their_own_role() {
+        let filters = Filters {
+            sub_roles: BTreeSet::from([SubRole::Hitscan]),
+            ..Filters::default()
+        };
+        let mut hitscan = hero(Role::Damage, false, false, false);
+        hitscan.sub_roles.insert(SubRole::Hitscan);
+        let mut flanker = hero(Role::Damage, false, false, false);
+        flanker.sub_roles.insert(SubRole::Flanker);
+        assert!(filters.is_selected(&hitscan, &[]));
+        assert!(!filters.is_selected(&flanker, &[]));
+        assert!(filters.is_selected(&hero(Role::Tank, false, false, false), &[]));
+    }
+
+    #[test]
+    fn tag_filters() {
+        let filters = Filters {
+            include_tags: BTreeSet::from(["fun".to_string()]),
+            exclude_tags: BTreeSet::from(["learning".to_string()]),
+            ..Filters::default()
+        };
+        let mut hero = hero(Role::Support, false, false, false);
+        assert!(!filters.is_selected(&hero, &[]));
+        hero.add_tag("fun");
+        assert!(filters.is_selected(&hero, &[]));
+        hero.add_tag("learning");
+        assert!(!filters.is_selected(&hero, &[]));
+    }
+
+    #[test]
+    fn expression_with_quoted_tag_deserializes() {
+        let mut filters = Filters {
+            include_tags: BTreeSet::from(["a\"b".to_string(), "c\\d".to_string()]),
+            ..Filters::default()
+        };
+        filters.expression = Some(filters.to_expression());
+        let json = serde_json::to_string(&filters).unwrap();
+        assert_eq!(serde_json::from_str::<Filters>(&json).unwrap(), filters);
+    }
+
+    #[test]
+    fn expression_matches_checkboxes() {
+        for flags in combinations(5) {
+            let filters = Filters {
+                tank: flags[0],
+                damage: flags[1],
+                support: flags[2],
+                favourite: flags[3],
+                stadium: flags[4],
+                sub_roles: BTreeSet::from([SubRole::MainTank]),
+                exclude_tags: BTreeSet::from(["learning".to_string()]),
+                ..Filters::default()
+            };
+            let expression = Filters {
+                expression: Some(filters.to_expression()),
+                ..Filters::default()
+            };
+            for hero_flags in combinations(3) {
+                for role in Role::ALL {
+                    let mut hero = hero(role, hero_flags[0], hero_flags[1], false);
+                    if hero_flags[2] {
+                        hero.add_tag("learning");
+                        hero.sub_roles.insert(SubRole::MainTank);
+                    }
+                    assert_eq!(
+                        filters.is_selected(&hero, &[]),
+                        expression.is_selected(&hero, &[]),
+                        "{} with {hero:?}",
+                        filters.to_expression()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+mod expression;
+mod file_changed;
+mod file_dialogs;
+mod hero_edit;
+mod history;
+mod load_error;
+mod presets;
+mod profiles;
+mod recent;
+mod team;
+mod undo;
+mod unsaved_changes;
+mod watcher;
+
+pub use expression::ExpressionEdit;
+pub use file_changed::{show_file_changed_dialog, FileChangedResponse};
+pub use file_dialogs::{csv_file_dialog, heroes_file_dialog};
+pub use hero_edit::{HeroEdit, HeroEditDialog, HeroEditResponse};
+pub use history::HistoryWindow;
+pub use load_error::{LoadErrorDialog, LoadErrorResponse};
+pub use presets::{preset_shortcut, FilterPreset, PresetDialog, PresetResponse};
+pub use profiles::{
+    load_profiles, save_profiles, Profile, ProfileAction, ProfileDialog, ProfileResponse,
+    DEFAULT_PROFILE,
+};
+pub use recent::RecentFiles;
+pub use team::{TeamSettings, TeamWindow};
+pub use undo::UndoStack;
+pub use unsaved_changes::{show_unsaved_changes_dialog, PendingAction, UnsavedChangesResponse};
+pub use watcher::{FileWatcher, WATCH_INTERVAL};
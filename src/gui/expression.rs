@@ -0,0 +1,64 @@
+use eframe::egui::{self, Color32, Ui};
+use pickme::{Expr, Filters};
+
+/// Text box editing the filter expression, keeping the text while it doesn't parse
+#[derive(Default)]
+pub struct ExpressionEdit {
+    text: String,
+    error: Option<String>,
+    /// Expression `text` was last synced with, to notice when the filters are replaced
+    shown: Option<Expr>,
+}
+
+impl ExpressionEdit {
+    pub fn show(&mut self, ui: &mut Ui, filters: &mut Filters) {
+        if filters.expression != self.shown {
+            self.text = filters
+                .expression
+                .as_ref()
+                .map_or_else(String::new, Expr::to_string);
+            self.error = None;
+            self.shown = filters.expression.clone();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Expression:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.text)
+                    .hint_text("role:tank or (favourite and level<5)")
+                    .desired_width(320.0),
+            );
+            if response.changed() {
+                if self.text.trim().is_empty() {
+                    filters.expression = None;
+                    self.error = None;
+                } else {
+                    match self.text.parse() {
+                        Ok(expression) => {
+                            filters.expression = Some(expression);
+                            self.error = None;
+                        }
+                        Err(err) => self.error = Some(format!("{err}")),
+                    }
+                }
+                self.shown = filters.expression.clone();
+            }
+            if ui
+                .button("From checkboxes")
+                .on_hover_text("Replace the expression with one matching the checkboxes")
+                .clicked()
+            {
+                filters.expression = Some(filters.to_expression());
+            }
+            if ui
+                .add_enabled(filters.expression.is_some(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                filters.expression = None;
+            }
+            if let Some(error) = &self.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use eframe::egui::{self, Context};
+
+pub enum FileChangedResponse {
+    Open,
+    /// Load the file from disk, discarding unsaved changes
+    Reload,
+    /// Keep the heroes in memory, overwriting the file on disk
+    Overwrite,
+    /// Combine the heroes in memory with the file on disk
+    Merge,
+}
+
+/// Ask what to do when the heroes file changed on disk while there are unsaved changes
+pub fn show_file_changed_dialog(ctx: &Context, path: &Path) -> FileChangedResponse {
+    let mut response = FileChangedResponse::Open;
+    egui::Modal::new(egui::Id::new("file_changed")).show(ctx, |ui| {
+        ui.heading("Heroes file changed");
+        ui.label(format!(
+            "'{}' was changed by another program while you have unsaved changes.",
+            path.display()
+        ));
+        ui.horizontal(|ui| {
+            if ui
+                .button("Reload")
+                .on_hover_text("Discard your changes and load the file")
+                .clicked()
+            {
+                response = FileChangedResponse::Reload;
+            }
+            if ui
+                .button("Keep mine")
+                .on_hover_text("Overwrite the file with your changes")
+                .clicked()
+            {
+                response = FileChangedResponse::Overwrite;
+            }
+            if ui
+                .button("Merge")
+                .on_hover_text(
+                    "Keep the highest level of each hero, and favourites or blacklists from either",
+                )
+                .clicked()
+            {
+                response = FileChangedResponse::Merge;
+            }
+        });
+    });
+    response
+}
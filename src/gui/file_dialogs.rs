@@ -0,0 +1,24 @@
+use pickme::Format;
+use rfd::FileDialog;
+
+/// File dialog for heroes files in any supported [`Format`]
+pub fn heroes_file_dialog() -> FileDialog {
+    let extensions: Vec<&str> = Format::ALL
+        .iter()
+        .flat_map(|format| format.extensions())
+        .copied()
+        .collect();
+    Format::ALL.iter().fold(
+        FileDialog::new()
+            .set_directory("./")
+            .add_filter("Heroes", &extensions),
+        |dialog, format| dialog.add_filter(format.to_string(), format.extensions()),
+    )
+}
+
+/// File dialog for CSV files
+pub fn csv_file_dialog() -> FileDialog {
+    FileDialog::new()
+        .set_directory("./")
+        .add_filter("CSV", &["csv"])
+}
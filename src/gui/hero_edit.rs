@@ -0,0 +1,128 @@
+use eframe::egui::{self, Context};
+use pickme::{Heroes, Role};
+
+/// Changes to a hero made in the [`HeroEditDialog`]
+pub struct HeroEdit {
+    /// Name of the hero before editing
+    pub name: String,
+    pub new_name: String,
+    pub role: Role,
+    pub stadium: bool,
+}
+
+pub enum HeroEditResponse {
+    Open,
+    Cancel,
+    Save(HeroEdit),
+    /// Delete the hero with this name
+    Delete(String),
+}
+
+/// Dialog to rename a hero, move it to another role, toggle its stadium flag or delete it
+pub struct HeroEditDialog {
+    name: String,
+    new_name: String,
+    role: Role,
+    stadium: bool,
+    /// Whether the hero is in the active roster, so it comes back if deleted or renamed
+    in_roster: bool,
+    confirm_delete: bool,
+}
+
+impl HeroEditDialog {
+    pub fn new(heroes: &Heroes, name: &str, in_roster: bool) -> Option<Self> {
+        let hero = heroes.iter().find(|hero| hero.name == name)?;
+        Some(Self {
+            name: hero.name.clone(),
+            new_name: hero.name.clone(),
+            role: hero.role,
+            stadium: hero.stadium,
+            in_roster,
+            confirm_delete: false,
+        })
+    }
+
+    /// Open the dialog asking to confirm deleting the hero
+    pub fn confirm_delete(mut self) -> Self {
+        self.confirm_delete = true;
+        self
+    }
+
+    pub fn show(&mut self, ctx: &Context, heroes: &Heroes) -> HeroEditResponse {
+        let mut response = HeroEditResponse::Open;
+        let title = if self.confirm_delete {
+            "Delete hero"
+        } else {
+            "Edit hero"
+        };
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.confirm_delete {
+                    ui.label(format!("Delete '{}' and its progress?", self.name));
+                    if self.in_roster {
+                        ui.label("The hero is in the roster, so it will be added back the next time the file is loaded.");
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Delete").clicked() {
+                            response = HeroEditResponse::Delete(self.name.clone());
+                        }
+                        if ui.button("Cancel").clicked() {
+                            response = HeroEditResponse::Cancel;
+                        }
+                    });
+                    return;
+                }
+
+                egui::Grid::new("hero_edit_grid").show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.new_name);
+                    ui.end_row();
+
+                    ui.label("Role:");
+                    egui::ComboBox::from_id_salt("hero_edit_role")
+                        .selected_text(self.role.to_string())
+                        .show_ui(ui, |ui| {
+                            for role in Role::ALL {
+                                ui.selectable_value(&mut self.role, role, role.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Stadium:");
+                    ui.checkbox(&mut self.stadium, "Available in stadium");
+                    ui.end_row();
+                });
+                let error = heroes.check_rename(&self.name, &self.new_name).err();
+                if let Some(error) = &error {
+                    ui.colored_label(egui::Color32::LIGHT_RED, error.to_string());
+                } else if self.in_roster && self.new_name.trim() != self.name {
+                    ui.label(format!(
+                        "'{}' is in the roster, so it will be added back as a new hero the next time the file is loaded.",
+                        self.name
+                    ));
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(error.is_none(), egui::Button::new("OK"))
+                        .clicked()
+                    {
+                        response = HeroEditResponse::Save(HeroEdit {
+                            name: self.name.clone(),
+                            new_name: self.new_name.trim().to_string(),
+                            role: self.role,
+                            stadium: self.stadium,
+                        });
+                    }
+                    if ui.button("Delete…").clicked() {
+                        self.confirm_delete = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        response = HeroEditResponse::Cancel;
+                    }
+                });
+            });
+        response
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt::Display;
+
+use eframe::egui::{self, Context};
+use pickme::{History, HistoryEntry, Outcome, Role};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum OutcomeFilter {
+    #[default]
+    Any,
+    /// Entries without a recorded outcome
+    Pending,
+    Is(Outcome),
+}
+
+impl OutcomeFilter {
+    fn matches(&self, outcome: Option<Outcome>) -> bool {
+        match self {
+            OutcomeFilter::Any => true,
+            OutcomeFilter::Pending => outcome.is_none(),
+            OutcomeFilter::Is(expected) => outcome == Some(*expected),
+        }
+    }
+}
+
+impl Display for OutcomeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutcomeFilter::Any => write!(f, "Any"),
+            OutcomeFilter::Pending => write!(f, "Pending"),
+            OutcomeFilter::Is(outcome) => write!(f, "{outcome}"),
+        }
+    }
+}
+
+/// Window to browse, filter and delete pick history entries
+#[derive(Default)]
+pub struct HistoryWindow {
+    pub open: bool,
+    search: String,
+    role: Option<Role>,
+    outcome: OutcomeFilter,
+}
+
+impl HistoryWindow {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        entry
+            .hero
+            .to_lowercase()
+            .contains(&self.search.to_lowercase())
+            && self.role.is_none_or(|role| role == entry.role)
+            && self.outcome.matches(entry.outcome)
+    }
+
+    /// Show the window if it is open, returning whether `history` was modified
+    pub fn show(&mut self, ctx: &Context, history: &mut History) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+        egui::Window::new("History")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Hero:");
+                    ui.text_edit_singleline(&mut self.search);
+                    egui::ComboBox::from_id_salt("history_role")
+                        .selected_text(self.role.map_or("Any role".to_string(), |r| r.to_string()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.role, None, "Any role");
+                            for role in Role::ALL {
+                                ui.selectable_value(&mut self.role, Some(role), role.to_string());
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("history_outcome")
+                        .selected_text(self.outcome.to_string())
+                        .show_ui(ui, |ui| {
+                            for outcome in [OutcomeFilter::Any, OutcomeFilter::Pending]
+                                .into_iter()
+                                .chain(Outcome::ALL.map(OutcomeFilter::Is))
+                            {
+                                ui.selectable_value(
+                                    &mut self.outcome,
+                                    outcome,
+                                    outcome.to_string(),
+                                );
+                            }
+                        });
+                });
+                ui.separator();
+
+                let mut remove = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("history_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, entry) in history.entries.iter_mut().enumerate().rev() {
+                                if !self.matches(entry) {
+                                    continue;
+                                }
+                                ui.label(entry.local_time());
+                                ui.label(&entry.hero)
+                                    .on_hover_text(format!("Filters: {}", entry.filters));
+                                ui.label(entry.role.to_string());
+                                egui::ComboBox::from_id_salt(("history_entry_outcome", index))
+                                    .selected_text(
+                                        entry.outcome.map_or("-".to_string(), |o| o.to_string()),
+                                    )
+                                    .show_ui(ui, |ui| {
+                                        changed |= ui
+                                            .selectable_value(&mut entry.outcome, None, "-")
+                                            .changed();
+                                        for outcome in Outcome::ALL {
+                                            changed |= ui
+                                                .selectable_value(
+                                                    &mut entry.outcome,
+                                                    Some(outcome),
+                                                    outcome.to_string(),
+                                                )
+                                                .changed();
+                                        }
+                                    });
+                                if ui.button("🗑").on_hover_text("Delete entry").clicked() {
+                                    remove = Some(index);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                if let Some(index) = remove {
+                    history.remove(index);
+                    changed = true;
+                }
+            });
+        self.open = open;
+        changed
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Context};
+use pickme::{list_backups, LoadError};
+
+pub enum LoadErrorResponse {
+    Open,
+    /// Try loading the same file again
+    Retry,
+    /// Choose a different heroes file
+    OpenOther,
+    /// Replace the heroes file with a backup and load it
+    Restore(PathBuf),
+    /// Keep the default heroes, overwriting the heroes file on the next save
+    UseDefaults,
+}
+
+/// Modal shown when the heroes file couldn't be loaded, until the user decides what to do about it
+pub struct LoadErrorDialog {
+    pub error: LoadError,
+    backups: Vec<PathBuf>,
+    selected_backup: usize,
+}
+
+impl LoadErrorDialog {
+    pub fn new(path: &Path, error: LoadError) -> Self {
+        Self {
+            error,
+            backups: list_backups(path),
+            selected_backup: 0,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, path: &Path) -> LoadErrorResponse {
+        let mut response = LoadErrorResponse::Open;
+        egui::Modal::new(egui::Id::new("load_error")).show(ctx, |ui| {
+            ui.heading("Could not load heroes");
+            ui.label(format!("File: {}", path.display()));
+            if let LoadError::Parse {
+                line: Some(line),
+                column: Some(column),
+                ..
+            } = &self.error
+            {
+                ui.strong(format!("Line {line}, column {column}"));
+            }
+            ui.colored_label(egui::Color32::LIGHT_RED, self.error.to_string());
+            ui.label("The file will not be saved until this is resolved.");
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Retry").clicked() {
+                    response = LoadErrorResponse::Retry;
+                }
+                if ui.button("Open another file..").clicked() {
+                    response = LoadErrorResponse::OpenOther;
+                }
+                if ui
+                    .button("Start with defaults")
+                    .on_hover_text("The file will be overwritten with the default heroes")
+                    .clicked()
+                {
+                    response = LoadErrorResponse::UseDefaults;
+                }
+            });
+            if !self.backups.is_empty() {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("load_error_backup")
+                        .selected_text(file_name(&self.backups[self.selected_backup]))
+                        .show_ui(ui, |ui| {
+                            for (index, backup) in self.backups.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut self.selected_backup,
+                                    index,
+                                    file_name(backup),
+                                );
+                            }
+                        });
+                    if ui.button("Restore backup").clicked() {
+                        response =
+                            LoadErrorResponse::Restore(self.backups[self.selected_backup].clone());
+                    }
+                });
+            }
+        });
+        response
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,74 @@
+use eframe::egui::{self, Context};
+use pickme::Filters;
+use serde::{Deserialize, Serialize};
+
+/// Number of presets that can be applied with a keyboard shortcut
+pub const PRESET_SHORTCUT_COUNT: usize = 9;
+
+/// Named set of filters that can be switched to in one click
+#[derive(Clone, Deserialize, Serialize)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filters: Filters,
+}
+
+/// Keyboard shortcut applying the preset at `index`, for the first [`PRESET_SHORTCUT_COUNT`] presets
+pub fn preset_shortcut(index: usize) -> Option<egui::KeyboardShortcut> {
+    const KEYS: [egui::Key; PRESET_SHORTCUT_COUNT] = [
+        egui::Key::Num1,
+        egui::Key::Num2,
+        egui::Key::Num3,
+        egui::Key::Num4,
+        egui::Key::Num5,
+        egui::Key::Num6,
+        egui::Key::Num7,
+        egui::Key::Num8,
+        egui::Key::Num9,
+    ];
+    KEYS.get(index)
+        .map(|key| egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, *key))
+}
+
+pub enum PresetResponse {
+    Open,
+    Cancel,
+    /// Save the current filters under this name
+    Save(String),
+}
+
+/// Dialog asking for the name to save the current filters as
+#[derive(Default)]
+pub struct PresetDialog {
+    name: String,
+}
+
+impl PresetDialog {
+    pub fn show(&mut self, ctx: &Context, presets: &[FilterPreset]) -> PresetResponse {
+        let mut response = PresetResponse::Open;
+        egui::Window::new("Save filter preset")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.name);
+                });
+                let name = self.name.trim();
+                if presets.iter().any(|preset| preset.name == name) {
+                    ui.label("Replaces the preset with this name");
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        response = PresetResponse::Save(name.to_string());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        response = PresetResponse::Cancel;
+                    }
+                });
+            });
+        response
+    }
+}
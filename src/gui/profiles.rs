@@ -0,0 +1,229 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Context};
+use pickme::{Filters, History, Roster};
+
+const KEY_PROFILES: &str = "profiles";
+const KEY_ACTIVE_PROFILE: &str = "active_profile";
+
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Named heroes file and filters, so several players can share one app instance
+#[derive(Clone)]
+pub struct Profile {
+    pub name: String,
+    pub file_path: PathBuf,
+    pub filters: Filters,
+}
+
+impl Profile {
+    pub fn new<S: Into<String>>(name: S, file_path: PathBuf, filters: Filters) -> Self {
+        Self {
+            name: name.into(),
+            file_path,
+            filters,
+        }
+    }
+
+    /// Heroes file for a new profile called `name`, in the same directory as `sibling`
+    pub fn file_path_for(name: &str, sibling: &Path) -> PathBuf {
+        let file_name: String = name
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        sibling.with_file_name(format!("{file_name}.yaml"))
+    }
+
+    /// Heroes file for a new profile called `name`, unless it would use an existing file, the file of
+    /// one of `profiles` or one reserved for the roster or the history of `sibling`
+    pub fn new_file_path(
+        name: &str,
+        sibling: &Path,
+        profiles: &[Profile],
+    ) -> Result<PathBuf, String> {
+        let file_path = Self::file_path_for(name, sibling);
+        if file_path == Roster::path_for(sibling) || file_path == History::path_for(sibling) {
+            Err(format!(
+                "'{}' is reserved, choose another name",
+                file_path.display()
+            ))
+        } else if file_path.exists()
+            || profiles
+                .iter()
+                .any(|profile| profile.file_path == file_path)
+        {
+            Err(format!("'{}' already exists", file_path.display()))
+        } else {
+            Ok(file_path)
+        }
+    }
+
+    fn key(name: &str, key: &str) -> String {
+        format!("profile/{name}/{key}")
+    }
+
+    fn load(storage: &dyn eframe::Storage, name: &str) -> Option<Self> {
+        let file_path = storage
+            .get_string(&Self::key(name, "file_path"))
+            .filter(|file_path| !file_path.is_empty())?;
+        let filters = storage
+            .get_string(&Self::key(name, "filters"))
+            .and_then(|string| serde_json::from_str(&string).ok())
+            .unwrap_or_default();
+        Some(Self::new(name, PathBuf::from(file_path), filters))
+    }
+
+    /// Clear the keys of the profile called `name`, as storage can't remove them
+    fn clear(storage: &mut dyn eframe::Storage, name: &str) {
+        for key in ["file_path", "filters"] {
+            storage.set_string(&Self::key(name, key), String::new());
+        }
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(
+            &Self::key(&self.name, "file_path"),
+            self.file_path.to_str().unwrap().to_string(),
+        );
+        storage.set_string(
+            &Self::key(&self.name, "filters"),
+            serde_json::to_string(&self.filters).expect("Unable to serialize filters"),
+        );
+    }
+}
+
+/// Load every profile and the index of the active one, or `None` if no profiles have been saved yet
+pub fn load_profiles(storage: &dyn eframe::Storage) -> Option<(Vec<Profile>, usize)> {
+    let names: Vec<String> = serde_json::from_str(&storage.get_string(KEY_PROFILES)?).ok()?;
+    let profiles: Vec<Profile> = names
+        .iter()
+        .filter_map(|name| Profile::load(storage, name))
+        .collect();
+    if profiles.is_empty() {
+        return None;
+    }
+    let active = storage
+        .get_string(KEY_ACTIVE_PROFILE)
+        .and_then(|name| profiles.iter().position(|profile| profile.name == name))
+        .unwrap_or(0);
+    Some((profiles, active))
+}
+
+/// Save every profile, clearing the keys of profiles that were renamed or deleted since the last save
+pub fn save_profiles(storage: &mut dyn eframe::Storage, profiles: &[Profile], active: usize) {
+    let names: Vec<&str> = profiles
+        .iter()
+        .map(|profile| profile.name.as_str())
+        .collect();
+    let saved_names: Vec<String> = storage
+        .get_string(KEY_PROFILES)
+        .and_then(|string| serde_json::from_str(&string).ok())
+        .unwrap_or_default();
+    for name in saved_names {
+        if !names.contains(&name.as_str()) {
+            Profile::clear(storage, &name);
+        }
+    }
+    storage.set_string(
+        KEY_PROFILES,
+        serde_json::to_string(&names).expect("Unable to serialize profiles"),
+    );
+    storage.set_string(KEY_ACTIVE_PROFILE, profiles[active].name.clone());
+    for profile in profiles {
+        profile.save(storage);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProfileAction {
+    Create,
+    Rename,
+    Duplicate,
+    Delete,
+}
+
+pub enum ProfileResponse {
+    Open,
+    Cancel,
+    Confirm(ProfileAction, String),
+}
+
+/// Dialog asking for a profile name, or confirmation before deleting a profile
+pub struct ProfileDialog {
+    action: ProfileAction,
+    name: String,
+}
+
+impl ProfileDialog {
+    pub fn new<S: Into<String>>(action: ProfileAction, name: S) -> Self {
+        Self {
+            action,
+            name: name.into(),
+        }
+    }
+
+    /// Show the dialog, where new profiles get a heroes file next to `file_path`
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        profiles: &[Profile],
+        file_path: &Path,
+    ) -> ProfileResponse {
+        let title = match self.action {
+            ProfileAction::Create => "New profile",
+            ProfileAction::Rename => "Rename profile",
+            ProfileAction::Duplicate => "Duplicate profile",
+            ProfileAction::Delete => "Delete profile",
+        };
+        let mut response = ProfileResponse::Open;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let error = if self.action == ProfileAction::Delete {
+                    ui.label(format!(
+                        "Delete profile '{}'? Its heroes file is kept on disk.",
+                        self.name
+                    ));
+                    None
+                } else {
+                    let new_file = matches!(
+                        self.action,
+                        ProfileAction::Create | ProfileAction::Duplicate
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut self.name);
+                    });
+                    let name = self.name.trim();
+                    if name.is_empty() {
+                        Some("Name can't be empty".to_string())
+                    } else if profiles.iter().any(|profile| profile.name == name) {
+                        Some("A profile with this name already exists".to_string())
+                    } else if new_file {
+                        Profile::new_file_path(name, file_path, profiles).err()
+                    } else {
+                        None
+                    }
+                };
+                if let Some(error) = &error {
+                    ui.colored_label(egui::Color32::LIGHT_RED, error);
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(error.is_none(), egui::Button::new("OK"))
+                        .clicked()
+                    {
+                        response =
+                            ProfileResponse::Confirm(self.action, self.name.trim().to_string());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        response = ProfileResponse::Cancel;
+                    }
+                });
+            });
+        response
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use pickme::Filters;
+use serde::{Deserialize, Serialize};
+
+const KEY_RECENT_FILES: &str = "recent_files";
+
+/// Number of files kept in the recent files list
+pub const RECENT_FILE_COUNT: usize = 10;
+
+/// Heroes file that was opened recently and the filters last used with it
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RecentFile {
+    pub file_path: PathBuf,
+    pub filters: Filters,
+}
+
+/// Recently opened heroes files, most recent first
+#[derive(Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct RecentFiles {
+    files: Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    pub fn load(storage: &dyn eframe::Storage) -> Self {
+        storage
+            .get_string(KEY_RECENT_FILES)
+            .and_then(|string| serde_json::from_str(&string).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(
+            KEY_RECENT_FILES,
+            serde_json::to_string(self).expect("Unable to serialize recent files"),
+        );
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecentFile> {
+        self.files.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Filters last used with the file at `file_path`
+    pub fn filters(&self, file_path: &Path) -> Option<&Filters> {
+        self.files
+            .iter()
+            .find(|file| file.file_path == file_path)
+            .map(|file| &file.filters)
+    }
+
+    /// Move `file_path` to the top of the list with the `filters` in use for it
+    pub fn remember(&mut self, file_path: &Path, filters: &Filters) {
+        self.files.retain(|file| file.file_path != file_path);
+        self.files.insert(
+            0,
+            RecentFile {
+                file_path: file_path.to_path_buf(),
+                filters: filters.clone(),
+            },
+        );
+        self.files.truncate(RECENT_FILE_COUNT);
+    }
+}
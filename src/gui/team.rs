@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Context};
+use pickme::{generate_team_with, load_heroes, Assignment, Composition, Filters, Heroes, Player};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::heroes_file_dialog;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TeamMember {
+    pub name: String,
+    pub file_path: PathBuf,
+}
+
+/// Team configuration persisted between sessions
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct TeamSettings {
+    pub members: Vec<TeamMember>,
+    pub composition: Composition,
+    /// Filters applied to every member's heroes, roles come from the composition
+    pub filters: Filters,
+}
+
+/// Window to generate a full team where every member gets a distinct hero
+#[derive(Default)]
+pub struct TeamWindow {
+    pub open: bool,
+    pub settings: TeamSettings,
+    result: Option<Result<Vec<Assignment>, String>>,
+}
+
+impl TeamWindow {
+    pub fn new(settings: TeamSettings) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Build the players, using `heroes` rather than reloading for a member whose file is `file_path`
+    fn players(&self, file_path: &Path, heroes: &Heroes) -> Result<Vec<Player>, String> {
+        self.settings
+            .members
+            .iter()
+            .map(|member| {
+                let heroes = if member.file_path == file_path {
+                    heroes.clone()
+                } else {
+                    load_heroes(&member.file_path)
+                        .map_err(|err| format!("{}: {err}", member.name))?
+                };
+                Ok(Player {
+                    name: member.name.clone(),
+                    heroes,
+                    filters: self.settings.filters.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Show the window, generating teams with `rng` so they follow the app's seed
+    pub fn show(&mut self, ctx: &Context, file_path: &Path, heroes: &Heroes, rng: &mut impl Rng) {
+        let mut open = self.open;
+        egui::Window::new("Team").open(&mut open).show(ctx, |ui| {
+            let mut remove = None;
+            egui::Grid::new("team_members").show(ui, |ui| {
+                for (index, member) in self.settings.members.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut member.name);
+                    ui.label(member.file_path.display().to_string());
+                    if ui
+                        .button("📂")
+                        .on_hover_text("Choose heroes file")
+                        .clicked()
+                    {
+                        if let Some(file_path) = heroes_file_dialog().pick_file() {
+                            member.file_path = file_path;
+                        }
+                    }
+                    if ui.button("🗑").on_hover_text("Remove player").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = remove {
+                self.settings.members.remove(index);
+            }
+            if ui.button("Add player").clicked() {
+                self.settings.members.push(TeamMember {
+                    name: format!("Player {}", self.settings.members.len() + 1),
+                    file_path: file_path.to_path_buf(),
+                });
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Composition:");
+                let composition = &mut self.settings.composition;
+                egui::ComboBox::from_id_salt("team_composition")
+                    .selected_text(composition.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            composition,
+                            Composition::ROLE_QUEUE_5V5,
+                            "1-2-2 (5v5)",
+                        );
+                        ui.selectable_value(
+                            composition,
+                            Composition::ROLE_QUEUE_6V6,
+                            "2-2-2 (6v6)",
+                        );
+                    });
+                let filters = &mut self.settings.filters;
+                ui.checkbox(&mut filters.favourite, "Favourite");
+                ui.checkbox(&mut filters.lowest, "Lowest");
+                ui.checkbox(&mut filters.stadium, "Stadium");
+                ui.checkbox(&mut filters.weighted, "Weighted");
+            });
+            if ui.button("Generate").clicked() {
+                self.result = Some(self.players(file_path, heroes).and_then(|players| {
+                    generate_team_with(&players, self.settings.composition, rng)
+                        .map_err(|err| err.to_string())
+                }));
+            }
+
+            match &self.result {
+                Some(Ok(assignments)) => {
+                    egui::Grid::new("team_assignments")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for assignment in assignments {
+                                ui.label(&assignment.player);
+                                ui.label(assignment.role.to_string());
+                                ui.strong(&assignment.hero.name);
+                                ui.end_row();
+                            }
+                        });
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::LIGHT_RED, err);
+                }
+                None => {}
+            }
+        });
+        self.open = open;
+    }
+}
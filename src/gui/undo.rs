@@ -0,0 +1,61 @@
+use std::mem;
+
+/// Number of edits that can be undone
+pub const UNDO_LIMIT: usize = 100;
+
+/// Snapshots of a value before each edit, so edits can be undone and redone
+pub struct UndoStack<T> {
+    /// The value after the last recorded edit
+    current: T,
+    undo: Vec<T>,
+    redo: Vec<T>,
+}
+
+impl<T: Clone> UndoStack<T> {
+    pub fn new(state: &T) -> Self {
+        Self {
+            current: state.clone(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Forget every edit, starting again from `state`
+    pub fn reset(&mut self, state: &T) {
+        *self = Self::new(state);
+    }
+
+    /// Record that the value was edited to `state`
+    pub fn record(&mut self, state: &T) {
+        self.undo
+            .push(mem::replace(&mut self.current, state.clone()));
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The value before the last edit, if any
+    pub fn undo(&mut self) -> Option<T> {
+        let previous = self.undo.pop()?;
+        self.redo
+            .push(mem::replace(&mut self.current, previous.clone()));
+        Some(previous)
+    }
+
+    /// The value after the last undone edit, if any
+    pub fn redo(&mut self) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo
+            .push(mem::replace(&mut self.current, next.clone()));
+        Some(next)
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Context};
+
+/// What to do once unsaved changes have been dealt with
+#[derive(Clone, PartialEq)]
+pub enum PendingAction {
+    New,
+    Open,
+    /// Open a file without asking for it, e.g. from the recent files
+    OpenFile(PathBuf),
+    Exit,
+}
+
+pub enum UnsavedChangesResponse {
+    Open,
+    /// Save the changes, then carry on
+    Save,
+    /// Carry on without saving
+    Discard,
+    Cancel,
+}
+
+/// Ask whether to save the changes to the heroes file before carrying on
+pub fn show_unsaved_changes_dialog(ctx: &Context, path: &Path) -> UnsavedChangesResponse {
+    let mut response = UnsavedChangesResponse::Open;
+    let modal = egui::Modal::new(egui::Id::new("unsaved_changes")).show(ctx, |ui| {
+        ui.heading("Unsaved changes");
+        ui.label(format!(
+            "Do you want to save the changes to '{}'?",
+            path.display()
+        ));
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                response = UnsavedChangesResponse::Save;
+            }
+            if ui.button("Don't save").clicked() {
+                response = UnsavedChangesResponse::Discard;
+            }
+            if ui.button("Cancel").clicked() {
+                response = UnsavedChangesResponse::Cancel;
+            }
+        });
+    });
+    if modal.should_close() && matches!(response, UnsavedChangesResponse::Open) {
+        response = UnsavedChangesResponse::Cancel;
+    }
+    response
+}
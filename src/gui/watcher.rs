@@ -0,0 +1,51 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often watched files are checked for changes
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Polls the modification time of a file to notice when it is changed by another program
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl FileWatcher {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: Self::modified(path),
+            last_check: Instant::now(),
+        }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Whether the file at `path` was modified since the last poll. Checks at most once per
+    /// [`WATCH_INTERVAL`], and starts watching afresh if `path` is a different file.
+    pub fn poll(&mut self, path: &Path) -> bool {
+        if path != self.path {
+            *self = Self::new(path);
+            return false;
+        }
+        if self.last_check.elapsed() < WATCH_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified = Self::modified(path);
+        if modified != self.modified {
+            self.modified = modified;
+            modified.is_some()
+        } else {
+            false
+        }
+    }
+}
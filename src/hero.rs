@@ -1,7 +1,13 @@
-use std::fmt::Display;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    str::FromStr,
+};
 
 use serde::{Deserialize, Serialize};
 
+use crate::Roster;
+
 #[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
 pub enum Role {
     Tank,
@@ -9,6 +15,126 @@ pub enum Role {
     Support,
 }
 
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Tank, Role::Damage, Role::Support];
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tank" => Ok(Role::Tank),
+            "damage" | "dps" => Ok(Role::Damage),
+            "support" => Ok(Role::Support),
+            _ => Err(format!("unknown role '{s}'")),
+        }
+    }
+}
+
+/// Finer grained classification of heroes within their role
+#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Debug, Hash, PartialOrd, Ord)]
+pub enum SubRole {
+    MainTank,
+    OffTank,
+    Hitscan,
+    Flanker,
+    MainHeal,
+    FlexSupport,
+}
+
+impl SubRole {
+    pub const ALL: [SubRole; 6] = [
+        SubRole::MainTank,
+        SubRole::OffTank,
+        SubRole::Hitscan,
+        SubRole::Flanker,
+        SubRole::MainHeal,
+        SubRole::FlexSupport,
+    ];
+
+    /// Role every hero with this sub-role belongs to
+    pub fn role(&self) -> Role {
+        match self {
+            SubRole::MainTank | SubRole::OffTank => Role::Tank,
+            SubRole::Hitscan | SubRole::Flanker => Role::Damage,
+            SubRole::MainHeal | SubRole::FlexSupport => Role::Support,
+        }
+    }
+}
+
+impl FromStr for SubRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace([' ', '-', '_'], "").as_str() {
+            "maintank" => Ok(SubRole::MainTank),
+            "offtank" => Ok(SubRole::OffTank),
+            "hitscan" => Ok(SubRole::Hitscan),
+            "flanker" => Ok(SubRole::Flanker),
+            "mainheal" => Ok(SubRole::MainHeal),
+            "flexsupport" => Ok(SubRole::FlexSupport),
+            _ => Err(format!("unknown sub-role '{s}'")),
+        }
+    }
+}
+
+impl Display for SubRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubRole::MainTank => write!(f, "Main Tank"),
+            SubRole::OffTank => write!(f, "Off Tank"),
+            SubRole::Hitscan => write!(f, "Hitscan"),
+            SubRole::Flanker => write!(f, "Flanker"),
+            SubRole::MainHeal => write!(f, "Main Heal"),
+            SubRole::FlexSupport => write!(f, "Flex Support"),
+        }
+    }
+}
+
+/// Game mode progress can be tracked in
+#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Debug, Hash, PartialOrd, Ord)]
+pub enum GameMode {
+    QuickPlay,
+    Competitive,
+    Stadium,
+    Arcade,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::QuickPlay,
+        GameMode::Competitive,
+        GameMode::Stadium,
+        GameMode::Arcade,
+    ];
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace([' ', '-', '_'], "").as_str() {
+            "quickplay" | "qp" => Ok(GameMode::QuickPlay),
+            "competitive" | "comp" => Ok(GameMode::Competitive),
+            "stadium" => Ok(GameMode::Stadium),
+            "arcade" => Ok(GameMode::Arcade),
+            _ => Err(format!("unknown game mode '{s}'")),
+        }
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameMode::QuickPlay => write!(f, "Quick Play"),
+            GameMode::Competitive => write!(f, "Competitive"),
+            GameMode::Stadium => write!(f, "Stadium"),
+            GameMode::Arcade => write!(f, "Arcade"),
+        }
+    }
+}
+
 impl Display for Role {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -19,103 +145,293 @@ impl Display for Role {
     }
 }
 
+/// Error editing a hero in [`Heroes`]
+#[derive(Debug, PartialEq)]
+pub enum EditError {
+    /// There is no hero with this name
+    NotFound(String),
+    /// Another hero already has this name
+    NameTaken(String),
+    EmptyName,
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditError::NotFound(name) => write!(f, "No hero named '{name}'"),
+            EditError::NameTaken(name) => write!(f, "A hero named '{name}' already exists"),
+            EditError::EmptyName => write!(f, "Hero name can't be empty"),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Heroes {
+    /// Schema version of the heroes file, see [`crate::schema`]
+    #[serde(default)]
+    pub version: u32,
     pub tanks: Vec<Hero>,
     pub damages: Vec<Hero>,
     pub supports: Vec<Hero>,
+    /// Heroes removed from the game, kept so their progress isn't lost
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archived: Vec<Hero>,
 }
 
 impl Heroes {
-    pub fn migrate(&mut self) {
-        let default = Self::default();
-        Self::add_missing(&mut self.tanks, &default.tanks);
-        Self::add_missing(&mut self.damages, &default.damages);
-        Self::add_missing(&mut self.supports, &default.supports);
-        Self::set_stadium(&mut self.tanks, &default.tanks);
-        Self::set_stadium(&mut self.damages, &default.damages);
-        Self::set_stadium(&mut self.supports, &default.supports);
-    }
-
-    /// Add any additional heroes in `new` to `old`
-    fn add_missing(old: &mut Vec<Hero>, new: &[Hero]) {
-        for hero in new {
-            if !old.contains(hero) {
-                println!("Adding new hero '{}'", hero.name);
-                old.push(hero.clone());
+    /// Iterate over every hero, tanks first, then damages, then supports
+    pub fn iter(&self) -> impl Iterator<Item = &Hero> {
+        self.tanks
+            .iter()
+            .chain(self.damages.iter())
+            .chain(self.supports.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Hero> {
+        self.tanks
+            .iter_mut()
+            .chain(self.damages.iter_mut())
+            .chain(self.supports.iter_mut())
+    }
+
+    pub fn role(&self, role: Role) -> &Vec<Hero> {
+        match role {
+            Role::Tank => &self.tanks,
+            Role::Damage => &self.damages,
+            Role::Support => &self.supports,
+        }
+    }
+
+    pub fn role_mut(&mut self, role: Role) -> &mut Vec<Hero> {
+        match role {
+            Role::Tank => &mut self.tanks,
+            Role::Damage => &mut self.damages,
+            Role::Support => &mut self.supports,
+        }
+    }
+
+    /// Add `hero` to the list for its role, keeping the list sorted by name
+    pub fn add(&mut self, hero: Hero) {
+        let heroes = self.role_mut(hero.role);
+        heroes.push(hero);
+        heroes.sort_unstable_by_key(|hero| hero.name.clone());
+    }
+
+    /// Combine `other` into these heroes: heroes in both keep the combined progress from
+    /// [`Hero::merge_progress`], and heroes only in `other` are added
+    pub fn merge(&mut self, other: &Heroes) {
+        for hero in other.iter() {
+            match self.role_mut(hero.role).iter_mut().find(|h| *h == hero) {
+                Some(existing) => existing.merge_progress(hero),
+                None => self.add(hero.clone()),
+            }
+        }
+    }
+
+    /// Reset every hero to level 1, forgetting their levels in each game mode
+    pub fn reset_levels(&mut self) {
+        for hero in self.iter_mut() {
+            hero.level = 1;
+            hero.mode_levels.clear();
+        }
+    }
+
+    /// Every tag used by any hero
+    pub fn tags(&self) -> BTreeSet<&str> {
+        self.iter()
+            .flat_map(|hero| hero.tags.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Find a hero by name, ignoring case
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Hero> {
+        self.iter_mut()
+            .find(|hero| hero.name.to_lowercase() == name.to_lowercase())
+    }
+
+    /// Remove the hero called `name`, ignoring case, from whichever role it is in
+    pub fn remove(&mut self, name: &str) -> Option<Hero> {
+        Role::ALL.into_iter().find_map(|role| {
+            let heroes = self.role_mut(role);
+            let index = heroes
+                .iter()
+                .position(|hero| hero.name.to_lowercase() == name.to_lowercase())?;
+            Some(heroes.remove(index))
+        })
+    }
+
+    /// Check that the hero called `name` can be renamed to `new_name`
+    pub fn check_rename(&self, name: &str, new_name: &str) -> Result<(), EditError> {
+        let hero = self
+            .iter()
+            .find(|hero| hero.name.to_lowercase() == name.to_lowercase())
+            .ok_or_else(|| EditError::NotFound(name.to_string()))?;
+        if hero.name.to_lowercase() == new_name.trim().to_lowercase() {
+            // Only the case changes
+            return Ok(());
+        }
+        self.check_new_name(new_name)
+    }
+
+    /// Check that a new hero can be called `name`: it isn't empty and no hero has it, ignoring case
+    pub fn check_new_name(&self, name: &str) -> Result<(), EditError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(EditError::EmptyName);
+        }
+        if self
+            .iter()
+            .any(|hero| hero.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(EditError::NameTaken(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Rename the hero called `name`, ignoring case, to `new_name`
+    ///
+    /// A hero renamed away from its roster name is added back by the next [`Heroes::migrate`].
+    pub fn rename_hero(&mut self, name: &str, new_name: &str) -> Result<(), EditError> {
+        self.check_rename(name, new_name)?;
+        let mut hero = self
+            .remove(name)
+            .ok_or_else(|| EditError::NotFound(name.to_string()))?;
+        hero.name = new_name.trim().to_string();
+        self.add(hero);
+        Ok(())
+    }
+
+    /// Move the hero called `name`, ignoring case, to the list for `role`
+    pub fn move_hero(&mut self, name: &str, role: Role) -> Result<(), EditError> {
+        let mut hero = self
+            .remove(name)
+            .ok_or_else(|| EditError::NotFound(name.to_string()))?;
+        if hero.role != role {
+            // Sub-roles belong to a role, so they don't carry over
+            hero.sub_roles.clear();
+        }
+        hero.role = role;
+        self.add(hero);
+        Ok(())
+    }
+
+    /// Bring the heroes up to date with `roster`
+    pub fn migrate(&mut self, roster: &Roster) {
+        self.rename(&roster.renames);
+        self.archive(&roster.removed);
+        let default = roster.heroes();
+        self.restore_archived(&default);
+        self.add_missing(&default);
+        self.set_stadium(&default);
+        self.set_sub_roles(&default);
+    }
+
+    /// Rename heroes using the `old name -> new name` table, merging progress into any hero already
+    /// using the new name
+    fn rename(&mut self, renames: &BTreeMap<String, String>) {
+        for role in Role::ALL {
+            let heroes = self.role_mut(role);
+            let mut index = 0;
+            while index < heroes.len() {
+                let Some(new_name) = renames.get(&heroes[index].name) else {
+                    index += 1;
+                    continue;
+                };
+                if let Some(existing) = heroes.iter().position(|h| &h.name == new_name) {
+                    eprintln!(
+                        "Merging '{}' into existing hero '{new_name}'",
+                        heroes[index].name
+                    );
+                    let old = heroes.remove(index);
+                    let existing = if existing > index {
+                        existing - 1
+                    } else {
+                        existing
+                    };
+                    heroes[existing].merge_progress(&old);
+                } else {
+                    eprintln!("Renaming '{}' to '{new_name}'", heroes[index].name);
+                    heroes[index].name = new_name.clone();
+                    index += 1;
+                }
+            }
+            heroes.sort_unstable_by_key(|h| h.name.clone());
+        }
+    }
+
+    /// Move heroes named in `removed` to the archive
+    fn archive(&mut self, removed: &[String]) {
+        for role in Role::ALL {
+            let heroes = self.role_mut(role);
+            let (retired, kept): (Vec<Hero>, Vec<Hero>) = heroes
+                .drain(..)
+                .partition(|hero| removed.contains(&hero.name));
+            *heroes = kept;
+            for hero in retired {
+                eprintln!("Archiving removed hero '{}'", hero.name);
+                self.archived.push(hero);
+            }
+        }
+    }
+
+    /// Move archived heroes that are back in `default` out of the archive
+    fn restore_archived(&mut self, default: &Heroes) {
+        let (restored, archived): (Vec<Hero>, Vec<Hero>) = self
+            .archived
+            .drain(..)
+            .partition(|hero| default.iter().any(|h| h == hero));
+        self.archived = archived;
+        for hero in restored {
+            eprintln!("Restoring archived hero '{}'", hero.name);
+            self.add(hero);
+        }
+    }
+
+    /// Add any heroes in `new` that aren't in any role yet, so heroes moved to another role aren't
+    /// added again
+    fn add_missing(&mut self, new: &Heroes) {
+        for hero in new.iter() {
+            if !self.iter().any(|h| h == hero) {
+                eprintln!("Adding new hero '{}'", hero.name);
+                self.add(hero.clone());
             }
         }
-        old.sort_unstable_by_key(|h| h.name.clone());
     }
 
-    /// Set stadium enabled for any hero in `old` where it is set in `new`
-    fn set_stadium(old: &mut Vec<Hero>, new: &[Hero]) {
-        for hero in old {
+    /// Set stadium enabled for any hero where it is set in `new`
+    fn set_stadium(&mut self, new: &Heroes) {
+        for hero in self.iter_mut() {
             if let Some(new_hero) = new.iter().find(|h| hero.name == h.name) {
                 if new_hero.stadium && !hero.stadium {
-                    println!("Setting stadium enable for '{}'", new_hero.name);
+                    eprintln!("Setting stadium enable for '{}'", new_hero.name);
                     hero.stadium |= new_hero.stadium;
                 }
             }
         }
     }
+
+    /// Set the sub-roles of any hero in `new` that has sub-roles to match it, unless the hero was moved
+    /// to another role
+    fn set_sub_roles(&mut self, new: &Heroes) {
+        for hero in self.iter_mut() {
+            if let Some(new_hero) = new
+                .iter()
+                .find(|h| hero.name == h.name && hero.role == h.role)
+            {
+                if !new_hero.sub_roles.is_empty() && new_hero.sub_roles != hero.sub_roles {
+                    eprintln!("Setting sub-roles for '{}'", new_hero.name);
+                    hero.sub_roles = new_hero.sub_roles.clone();
+                }
+            }
+        }
+    }
 }
 
 impl Default for Heroes {
     fn default() -> Self {
-        Self {
-            tanks: vec![
-                Hero::new("D.va", Role::Tank, true),
-                Hero::new("Doomfist", Role::Tank, true),
-                Hero::new("Hazard", Role::Tank, true),
-                Hero::new("Junker Queen", Role::Tank, true),
-                Hero::new("Mauga", Role::Tank, false),
-                Hero::new("Orisa", Role::Tank, true),
-                Hero::new("Ramatra", Role::Tank, false),
-                Hero::new("Reinhardt", Role::Tank, true),
-                Hero::new("Roadhog", Role::Tank, false),
-                Hero::new("Sigma", Role::Tank, true),
-                Hero::new("Winston", Role::Tank, true),
-                Hero::new("Wrecking Ball", Role::Tank, false),
-                Hero::new("Zarya", Role::Tank, true),
-            ],
-            damages: vec![
-                Hero::new("Ashe", Role::Damage, true),
-                Hero::new("Bastion", Role::Damage, false),
-                Hero::new("Cassidy", Role::Damage, true),
-                Hero::new("Echo", Role::Damage, false),
-                Hero::new("Freya", Role::Damage, true),
-                Hero::new("Genji", Role::Damage, true),
-                Hero::new("Hanzo", Role::Damage, false),
-                Hero::new("Junkrat", Role::Damage, true),
-                Hero::new("Mei", Role::Damage, true),
-                Hero::new("Pharah", Role::Damage, true),
-                Hero::new("Reaper", Role::Damage, true),
-                Hero::new("Sojourn", Role::Damage, true),
-                Hero::new("Soldier: 76", Role::Damage, true),
-                Hero::new("Sombra", Role::Damage, false),
-                Hero::new("Symmetra", Role::Damage, false),
-                Hero::new("Törbjorn", Role::Damage, true),
-                Hero::new("Tracer", Role::Damage, true),
-                Hero::new("Venture", Role::Damage, false),
-                Hero::new("Widowmaker", Role::Damage, false),
-                Hero::new("Vendeta", Role::Damage, false),
-            ],
-            supports: vec![
-                Hero::new("Ana", Role::Support, true),
-                Hero::new("Baptiste", Role::Support, false),
-                Hero::new("Brigitte", Role::Support, true),
-                Hero::new("Illari", Role::Support, false),
-                Hero::new("Juno", Role::Support, true),
-                Hero::new("Kiriko", Role::Support, true),
-                Hero::new("Lifeweaver", Role::Support, false),
-                Hero::new("Lúcio", Role::Support, true),
-                Hero::new("Mercy", Role::Support, true),
-                Hero::new("Moira", Role::Support, true),
-                Hero::new("Wuyang", Role::Support, true),
-                Hero::new("Zenyatta", Role::Support, true),
-            ],
-        }
+        Roster::embedded().heroes()
     }
 }
 
@@ -128,6 +444,14 @@ pub struct Hero {
     pub favourite: bool,
     #[serde(default)]
     pub blacklisted: bool,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub sub_roles: BTreeSet<SubRole>,
+    /// Free-form labels such as "dive" or "learning", see [`Hero::add_tag`]
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Level in each game mode, tracked alongside the overall `level`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mode_levels: BTreeMap<GameMode, u32>,
 }
 
 impl Hero {
@@ -139,6 +463,9 @@ impl Hero {
             stadium,
             role,
             blacklisted: false,
+            sub_roles: BTreeSet::new(),
+            tags: BTreeSet::new(),
+            mode_levels: BTreeMap::new(),
         }
     }
 
@@ -146,6 +473,51 @@ impl Hero {
         self.level += 1;
     }
 
+    /// Level in `mode`, or the overall level if there is no mode
+    pub fn level_in(&self, mode: Option<GameMode>) -> u32 {
+        match mode {
+            Some(mode) => self.mode_levels.get(&mode).copied().unwrap_or_default(),
+            None => self.level,
+        }
+    }
+
+    /// Level up overall and in `mode`
+    pub fn level_up_in(&mut self, mode: Option<GameMode>) {
+        self.level_up();
+        if let Some(mode) = mode {
+            *self.mode_levels.entry(mode).or_default() += 1;
+        }
+    }
+
+    /// Level down overall, never going below level 1
+    pub fn level_down(&mut self) {
+        self.level = self.level.saturating_sub(1).max(1);
+    }
+
+    /// Level down in `mode`, never going below 0, or overall if there is no mode
+    pub fn level_down_in(&mut self, mode: Option<GameMode>) {
+        match mode {
+            Some(mode) => {
+                let level = self.level_in(Some(mode));
+                self.set_level_in(Some(mode), level.saturating_sub(1));
+            }
+            None => self.level_down(),
+        }
+    }
+
+    /// Set the level in `mode`, or the overall level (at least 1) if there is no mode
+    pub fn set_level_in(&mut self, mode: Option<GameMode>, level: u32) {
+        match mode {
+            Some(mode) if level == 0 => {
+                self.mode_levels.remove(&mode);
+            }
+            Some(mode) => {
+                self.mode_levels.insert(mode, level);
+            }
+            None => self.level = level.max(1),
+        }
+    }
+
     pub fn toggle_favourite(&mut self) {
         self.favourite = !self.favourite;
     }
@@ -153,6 +525,35 @@ impl Hero {
     pub fn toggle_blacklisted(&mut self) {
         self.blacklisted = !self.blacklisted;
     }
+
+    /// Tags are trimmed and lowercased so they match regardless of how they were typed
+    pub fn normalize_tag(tag: &str) -> String {
+        tag.trim().to_lowercase()
+    }
+
+    /// Add `tag`, returning whether it was new
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = Self::normalize_tag(tag);
+        !tag.is_empty() && self.tags.insert(tag)
+    }
+
+    /// Remove `tag`, returning whether the hero had it
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        self.tags.remove(&Self::normalize_tag(tag))
+    }
+
+    /// Combine the progress of `other` into this hero, keeping the highest level and any favourite or
+    /// blacklist flag
+    pub fn merge_progress(&mut self, other: &Hero) {
+        self.level = self.level.max(other.level);
+        for (mode, level) in &other.mode_levels {
+            let mode_level = self.mode_levels.entry(*mode).or_default();
+            *mode_level = (*mode_level).max(*level);
+        }
+        self.favourite |= other.favourite;
+        self.blacklisted |= other.blacklisted;
+        self.tags.extend(other.tags.iter().cloned());
+    }
 }
 
 impl Display for Hero {
@@ -166,3 +567,112 @@ impl PartialEq for Hero {
         self.name == other.name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RosterEntry;
+
+    fn entry(name: &str, role: Role, stadium: bool) -> RosterEntry {
+        RosterEntry {
+            name: name.to_string(),
+            role,
+            stadium,
+            sub_roles: Vec::new(),
+            release_date: None,
+        }
+    }
+
+    fn roster(entries: Vec<RosterEntry>) -> Roster {
+        Roster {
+            heroes: entries,
+            renames: BTreeMap::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    fn names(heroes: &[Hero]) -> Vec<&str> {
+        heroes.iter().map(|hero| hero.name.as_str()).collect()
+    }
+
+    #[test]
+    fn migrate_adds_missing_heroes() {
+        let mut heroes = roster(vec![entry("Reinhardt", Role::Tank, false)]).heroes();
+        heroes.tanks[0].level = 5;
+        heroes.migrate(&roster(vec![
+            entry("Reinhardt", Role::Tank, false),
+            entry("Mauga", Role::Tank, false),
+            entry("Ana", Role::Support, true),
+        ]));
+        assert_eq!(names(&heroes.tanks), ["Mauga", "Reinhardt"]);
+        assert_eq!(names(&heroes.supports), ["Ana"]);
+        assert!(heroes.damages.is_empty());
+        assert_eq!(heroes.find_mut("Reinhardt").unwrap().level, 5);
+        assert_eq!(heroes.find_mut("Mauga").unwrap().level, 1);
+    }
+
+    #[test]
+    fn migrate_keeps_heroes_moved_to_another_role() {
+        let roster = roster(vec![entry("Symmetra", Role::Support, false)]);
+        let mut heroes = roster.heroes();
+        heroes.move_hero("Symmetra", Role::Damage).unwrap();
+        heroes.migrate(&roster);
+        assert_eq!(names(&heroes.damages), ["Symmetra"]);
+        assert!(heroes.supports.is_empty());
+    }
+
+    #[test]
+    fn migrate_only_enables_stadium() {
+        let mut heroes = roster(vec![
+            entry("Ana", Role::Support, false),
+            entry("Lucio", Role::Support, true),
+            entry("Mercy", Role::Support, false),
+        ])
+        .heroes();
+        heroes.migrate(&roster(vec![
+            entry("Ana", Role::Support, true),
+            entry("Lucio", Role::Support, false),
+            entry("Mercy", Role::Support, false),
+        ]));
+        let stadium: Vec<bool> = heroes.supports.iter().map(|hero| hero.stadium).collect();
+        assert_eq!(stadium, [true, true, false]);
+    }
+
+    #[test]
+    fn migrate_renames_and_archives_heroes() {
+        let mut heroes = roster(vec![
+            entry("Soldier", Role::Damage, false),
+            entry("Retired", Role::Damage, false),
+        ])
+        .heroes();
+        heroes.find_mut("Soldier").unwrap().level = 3;
+        let mut new = roster(vec![entry("Soldier: 76", Role::Damage, false)]);
+        new.renames
+            .insert("Soldier".to_string(), "Soldier: 76".to_string());
+        new.removed.push("Retired".to_string());
+        heroes.migrate(&new);
+        assert_eq!(names(&heroes.damages), ["Soldier: 76"]);
+        assert_eq!(heroes.damages[0].level, 3);
+        assert_eq!(names(&heroes.archived), ["Retired"]);
+    }
+
+    #[test]
+    fn level_down_in_lowers_only_the_mode_level() {
+        let mut hero = Hero::new("Ana", Role::Support, false);
+        hero.set_level_in(Some(GameMode::Arcade), 3);
+        hero.level_down_in(Some(GameMode::Arcade));
+        assert_eq!(hero.level, 1);
+        assert_eq!(hero.level_in(Some(GameMode::Arcade)), 2);
+
+        hero.set_level_in(Some(GameMode::Arcade), 1);
+        hero.level_down_in(Some(GameMode::Arcade));
+        hero.level_down_in(Some(GameMode::Arcade));
+        assert_eq!(hero.level_in(Some(GameMode::Arcade)), 0);
+        assert!(!hero.mode_levels.contains_key(&GameMode::Arcade));
+
+        hero.level = 2;
+        hero.level_down_in(None);
+        hero.level_down_in(None);
+        assert_eq!(hero.level, 1);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{file::write_atomic, Filters, Hero, Role};
+
+/// Result of a game played with a picked hero
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Win, Outcome::Loss, Outcome::Draw];
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "win" => Ok(Outcome::Win),
+            "loss" => Ok(Outcome::Loss),
+            "draw" => Ok(Outcome::Draw),
+            _ => Err(format!("unknown outcome '{s}'")),
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Win => write!(f, "Win"),
+            Outcome::Loss => write!(f, "Loss"),
+            Outcome::Draw => write!(f, "Draw"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub hero: String,
+    pub role: Role,
+    /// Filters that were active when the hero was picked
+    pub filters: Filters,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+}
+
+impl HistoryEntry {
+    pub fn new(hero: &Hero, filters: &Filters) -> Self {
+        Self {
+            hero: hero.name.clone(),
+            role: hero.role,
+            filters: filters.clone(),
+            timestamp: Utc::now(),
+            outcome: None,
+        }
+    }
+
+    /// Timestamp formatted in the local time zone
+    pub fn local_time(&self) -> String {
+        self.timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+}
+
+impl Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} ({})", self.local_time(), self.hero, self.role)?;
+        if let Some(outcome) = self.outcome {
+            write!(f, " - {outcome}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Log of picked heroes, oldest first
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Path of the history file kept next to the heroes file at `heroes_path`,
+    /// e.g. `heroes.yaml` has its history in `heroes.history.yaml`
+    pub fn path_for(heroes_path: &Path) -> PathBuf {
+        let stem = heroes_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "heroes".to_string());
+        heroes_path.with_file_name(format!("{stem}.history.yaml"))
+    }
+
+    /// Load the history from `path`, starting a new history if the file is missing
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        serde_yaml::from_reader(file).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_yaml::to_string(self).map_err(io::Error::other)?;
+        write_atomic(path, contents.as_bytes())
+    }
+
+    /// Add an entry for `hero` picked with `filters`
+    pub fn record(&mut self, hero: &Hero, filters: &Filters) {
+        self.entries.push(HistoryEntry::new(hero, filters));
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut HistoryEntry> {
+        self.entries.last_mut()
+    }
+
+    pub fn remove(&mut self, index: usize) -> HistoryEntry {
+        self.entries.remove(index)
+    }
+}
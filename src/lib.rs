@@ -0,0 +1,32 @@
+//! Hero picker core shared by the GUI and command-line front ends.
+
+mod backup;
+mod expr;
+mod file;
+mod filters;
+mod hero;
+mod history;
+mod picker;
+mod roster;
+pub mod schema;
+mod table;
+mod team;
+mod weights;
+
+pub use backup::{
+    create_backup, create_timestamped_backup, list_backups, restore_backup, rotate_backups,
+    BACKUP_COUNT, BACKUP_INTERVAL,
+};
+pub use expr::{Comparison, Expr, ParseError};
+pub use file::{
+    load_heroes, load_heroes_with_roster, save_heroes, Format, LoadError, SaveError,
+    DEFAULT_FILE_PATH,
+};
+pub use filters::Filters;
+pub use hero::{EditError, GameMode, Hero, Heroes, Role, SubRole};
+pub use history::{History, HistoryEntry, Outcome};
+pub use picker::{Pick, Picker};
+pub use roster::{Roster, RosterEntry, ROSTER_FILE_NAME};
+pub use table::{export_csv, import_csv, CsvError};
+pub use team::{generate_team, generate_team_with, Assignment, Composition, Player, TeamError};
+pub use weights::{WeightCurve, Weights};
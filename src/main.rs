@@ -1,68 +1,67 @@
 use eframe::egui::{self, Color32, RichText, Ui};
-use hero::*;
-use rand::prelude::SliceRandom;
-use rfd::FileDialog;
-use serde::{Deserialize, Serialize};
+use gui::{
+    csv_file_dialog, heroes_file_dialog, load_profiles, preset_shortcut, save_profiles,
+    show_file_changed_dialog, show_unsaved_changes_dialog, ExpressionEdit, FileChangedResponse,
+    FileWatcher, FilterPreset, HeroEdit, HeroEditDialog, HeroEditResponse, HistoryWindow,
+    LoadErrorDialog, LoadErrorResponse, PendingAction, PresetDialog, PresetResponse, Profile,
+    ProfileAction, ProfileDialog, ProfileResponse, RecentFiles, TeamSettings, TeamWindow,
+    UndoStack, UnsavedChangesResponse, DEFAULT_PROFILE, WATCH_INTERVAL,
+};
+use pickme::*;
+use rand::{rngs::StdRng, SeedableRng};
 use std::{
-    fs::File,
+    fmt::Display,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
-mod hero;
+mod gui;
 
 const KEY_FILTERS: &str = "filters";
+const KEY_FILTER_PRESETS: &str = "filter_presets";
 const KEY_FILE_PATH: &str = "file_path";
+const KEY_TEAM: &str = "team";
+const KEY_AUTOSAVE: &str = "autosave";
 
-const DEFAULT_FILE_PATH: &str = "heroes.yaml";
-
-#[derive(Serialize, Deserialize)]
-struct Filters {
-    tank: bool,
-    damage: bool,
-    support: bool,
-    favourite: bool,
-    lowest: bool,
-    #[serde(default = "Default::default")]
-    unique: bool,
-    #[serde(default)]
-    stadium: bool,
-}
+const SAVE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::S);
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+const REDO_SHORTCUT_ALT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
 
-impl Filters {
-    /// # Panics
-    /// Panics if `persistence` feature of eframe isn't enabled, or there was an error deserializing filters
-    fn load(cc: &eframe::CreationContext<'_>) -> Self {
-        let storage = cc.storage.expect("Persistence feature is not enabled");
-        match storage.get_string(KEY_FILTERS) {
-            None => Self::default(),
-            Some(string) => serde_json::from_str(&string).expect("Unable to deserialize filters"),
-        }
+/// # Panics
+/// Panics if there was an error deserializing filters
+fn load_filters(storage: &dyn eframe::Storage) -> Filters {
+    match storage.get_string(KEY_FILTERS) {
+        None => Filters::default(),
+        Some(string) => serde_json::from_str(&string).expect("Unable to deserialize filters"),
     }
+}
 
-    fn is_selected(&self, hero: &Hero, role: Role, session_picked: &[Hero]) -> bool {
-        (hero.favourite || !self.favourite)
-            && ((self.tank && role == Role::Tank)
-                || (self.support && role == Role::Support)
-                || (self.damage && role == Role::Damage))
-            && !(self.unique && session_picked.contains(hero))
-            && (if self.stadium { hero.stadium } else { true })
-            && !hero.blacklisted
-    }
+fn load_filter_presets(storage: &dyn eframe::Storage) -> Vec<FilterPreset> {
+    storage
+        .get_string(KEY_FILTER_PRESETS)
+        .and_then(|string| serde_json::from_str(&string).ok())
+        .unwrap_or_default()
 }
 
-impl Default for Filters {
-    fn default() -> Self {
-        Self {
-            tank: true,
-            damage: true,
-            support: true,
-            favourite: false,
-            lowest: false,
-            unique: false,
-            stadium: false,
-        }
-    }
+fn load_team_settings(storage: &dyn eframe::Storage) -> TeamSettings {
+    storage
+        .get_string(KEY_TEAM)
+        .and_then(|string| serde_json::from_str(&string).ok())
+        .unwrap_or_default()
+}
+
+fn load_autosave(storage: &dyn eframe::Storage) -> bool {
+    storage
+        .get_string(KEY_AUTOSAVE)
+        .and_then(|string| serde_json::from_str(&string).ok())
+        .unwrap_or(true)
 }
 
 fn main() -> Result<(), eframe::Error> {
@@ -73,76 +72,593 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
+/// What happened to the hero rows drawn in a frame
+#[derive(Default)]
+struct RowResponse {
+    /// Whether any hero was changed
+    changed: bool,
+    /// Hero to open the edit dialog for, and whether to go straight to deleting it
+    edit: Option<(String, bool)>,
+}
+
+impl RowResponse {
+    fn merge(&mut self, other: RowResponse) {
+        self.changed |= other.changed;
+        if other.edit.is_some() {
+            self.edit = other.edit;
+        }
+    }
+}
+
 struct PickMeApp {
     heroes: Heroes,
-    picked: Option<String>,
+    picked: Option<Pick>,
     filters: Filters,
     file_path: PathBuf,
     show_add_hero_dialog: bool,
     hero_name: String,
     role: Role,
-    session_picked: Vec<Hero>,
+    picker: Picker,
+    history: History,
+    /// Set when the history file failed to load, in which case it isn't saved so its entries aren't lost
+    history_load_failed: bool,
+    history_window: HistoryWindow,
+    team_window: TeamWindow,
+    profiles: Vec<Profile>,
+    /// Index of the profile in use. Its file path and filters are kept up to date in `file_path` and `filters`
+    active_profile: usize,
+    profile_dialog: Option<ProfileDialog>,
+    /// Set when the heroes file failed to load, in which case it isn't saved
+    load_error: Option<LoadErrorDialog>,
+    /// Last error saving or restoring a file, shown at the bottom of the window
+    error_message: Option<String>,
+    /// Save the heroes file after every change
+    autosave: bool,
+    /// Whether `heroes` has changes that aren't in the heroes file yet
+    unsaved_changes: bool,
+    heroes_watcher: FileWatcher,
+    history_watcher: FileWatcher,
+    /// Set when the heroes file changed on disk while there were unsaved changes, in which case it
+    /// isn't saved until the user decides what to keep
+    file_changed: bool,
+    /// Action waiting for the user to decide what to do with unsaved changes
+    pending_action: Option<PendingAction>,
+    /// Set once the user agreed to exit, so closing the window isn't interrupted again
+    exiting: bool,
+    /// Window title last sent to the viewport
+    title: String,
+    recent_files: RecentFiles,
+    /// Edits to `heroes` since the heroes file was loaded
+    undo_stack: UndoStack<Heroes>,
+    hero_edit_dialog: Option<HeroEditDialog>,
+    expression_edit: ExpressionEdit,
+    filter_presets: Vec<FilterPreset>,
+    preset_dialog: Option<PresetDialog>,
+    /// Seed picks are made from, for reproducible rolls
+    seed: Option<u64>,
+    rng: StdRng,
 }
 
 impl PickMeApp {
+    /// # Panics
+    /// Panics if `persistence` feature of eframe isn't enabled
     fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let file_path = Self::load_file_path(cc);
-        let heroes = Self::load_heroes(&file_path);
+        Self::with_storage(cc.storage.expect("Persistence feature is not enabled"))
+    }
 
-        Self {
-            heroes,
+    /// App with the settings in `storage`, loading the heroes file of the active profile
+    fn with_storage(storage: &dyn eframe::Storage) -> Self {
+        let (profiles, active_profile) = load_profiles(storage).unwrap_or_else(|| {
+            let profile = Profile::new(
+                DEFAULT_PROFILE,
+                Self::load_file_path(storage),
+                load_filters(storage),
+            );
+            (vec![profile], 0)
+        });
+        let file_path = profiles[active_profile].file_path.clone();
+        let filters = profiles[active_profile].filters.clone();
+        let history_path = History::path_for(&file_path);
+
+        let mut app = Self {
+            heroes: Heroes::default(),
             picked: None,
-            filters: Filters::load(cc),
-            file_path,
+            filters,
+            file_path: file_path.clone(),
             show_add_hero_dialog: false,
             hero_name: String::new(),
             role: Role::Tank,
-            session_picked: Vec::new(),
+            picker: Picker::new(),
+            history: History::default(),
+            history_load_failed: false,
+            history_window: HistoryWindow::default(),
+            team_window: TeamWindow::new(load_team_settings(storage)),
+            profiles,
+            active_profile,
+            profile_dialog: None,
+            load_error: None,
+            error_message: None,
+            autosave: load_autosave(storage),
+            unsaved_changes: false,
+            heroes_watcher: FileWatcher::new(&file_path),
+            history_watcher: FileWatcher::new(&history_path),
+            file_changed: false,
+            pending_action: None,
+            exiting: false,
+            title: String::new(),
+            recent_files: RecentFiles::load(storage),
+            undo_stack: UndoStack::new(&Heroes::default()),
+            hero_edit_dialog: None,
+            expression_edit: ExpressionEdit::default(),
+            filter_presets: load_filter_presets(storage),
+            preset_dialog: None,
+            seed: None,
+            rng: StdRng::from_entropy(),
+        };
+        app.remember_file();
+        app.load_heroes_file();
+        app.load_history();
+        app
+    }
+
+    /// Load heroes from `file_path`. If that fails, the default heroes are shown and the file isn't
+    /// saved until the error is resolved
+    fn load_heroes_file(&mut self) {
+        self.file_changed = false;
+        match load_heroes(&self.file_path) {
+            Ok(heroes) => {
+                self.heroes = heroes;
+                self.load_error = None;
+                self.unsaved_changes = false;
+            }
+            Err(err) => {
+                println!(
+                    "Unable to load heroes from '{}': {err}",
+                    self.file_path.display()
+                );
+                self.heroes = Roster::active(&self.file_path).heroes();
+                self.load_error = Some(LoadErrorDialog::new(&self.file_path, err));
+                self.unsaved_changes = false;
+            }
         }
+        self.undo_stack.reset(&self.heroes);
     }
 
-    fn load_file_path(cc: &eframe::CreationContext<'_>) -> PathBuf {
-        let storage = cc.storage.expect("Persistence feature is not enabled");
-        if let Some(file_path) = storage.get_string(KEY_FILE_PATH) {
-            println!("Retrieved file path from storage: {file_path}");
-            PathBuf::from(file_path)
+    /// Start a new heroes file with the default heroes
+    fn new_file_dialog(&mut self) {
+        let Some(file_path) = heroes_file_dialog()
+            .set_file_name(DEFAULT_FILE_PATH)
+            .save_file()
+        else {
+            println!("No file selected");
+            return;
+        };
+        println!("Creating new heroes file: {}", file_path.display());
+        self.heroes = Roster::active(&file_path).heroes();
+        self.change_file(file_path);
+        self.load_history();
+        self.load_error = None;
+        self.file_changed = false;
+        self.undo_stack.reset(&self.heroes);
+        self.mark_unsaved();
+    }
+
+    /// Write the heroes and history to a new file and keep using it
+    fn save_as_dialog(&mut self) {
+        let Some(file_path) = heroes_file_dialog()
+            .set_file_name(DEFAULT_FILE_PATH)
+            .save_file()
+        else {
+            println!("No file selected");
+            return;
+        };
+        println!("Saving heroes into new file: {}", file_path.display());
+        self.remember_file();
+        self.file_path = file_path;
+        self.remember_file();
+        self.load_error = None;
+        self.file_changed = false;
+        if self.save_heroes_file() {
+            self.save_history();
+        }
+    }
+
+    /// Run `action`, first asking what to do with any unsaved changes
+    fn request_action(&mut self, ctx: &egui::Context, action: PendingAction) {
+        if self.unsaved_changes {
+            self.pending_action = Some(action);
         } else {
-            println!("Loading from default file path");
-            PathBuf::from_str(DEFAULT_FILE_PATH).unwrap()
+            self.run_action(ctx, action);
         }
     }
 
-    fn load_heroes(path: &Path) -> Heroes {
-        if path.exists() {
-            if let Ok(file) = File::open(path) {
-                if let Ok(mut heroes) = serde_yaml::from_reader::<File, Heroes>(file) {
-                    heroes.migrate();
-                    heroes
-                } else {
-                    println!("Could not parse heroes file, loading defaults");
-                    Heroes::default()
+    fn run_action(&mut self, ctx: &egui::Context, action: PendingAction) {
+        match action {
+            PendingAction::New => self.new_file_dialog(),
+            PendingAction::Open => self.open_file_dialog(),
+            PendingAction::OpenFile(file_path) => self.open_file(file_path),
+            PendingAction::Exit => {
+                self.exiting = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+    }
+
+    /// Show the file name in the window title, marking it when there are unsaved changes
+    fn update_title(&mut self, ctx: &egui::Context) {
+        let file_name = self
+            .file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let dirty = if self.unsaved_changes { "*" } else { "" };
+        let title = format!("{file_name}{dirty} - Pick Me");
+        if title != self.title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+    }
+
+    /// Save heroes to `file_path`, returning whether the file is up to date
+    fn save_heroes_file(&mut self) -> bool {
+        if self.load_error.is_some() {
+            println!(
+                "Not saving heroes to '{}' as it failed to load",
+                self.file_path.display()
+            );
+            return false;
+        }
+        if self.file_changed {
+            println!(
+                "Not saving heroes to '{}' as it changed on disk",
+                self.file_path.display()
+            );
+            return false;
+        }
+        let saved = self.report(save_heroes(&self.file_path, &self.heroes));
+        if saved {
+            self.unsaved_changes = false;
+        }
+        saved
+    }
+
+    /// Note that `heroes` was changed, saving it if autosave is enabled
+    fn heroes_changed(&mut self) {
+        self.undo_stack.record(&self.heroes);
+        self.mark_unsaved();
+    }
+
+    /// Note that `heroes` differs from the heroes file, saving it if autosave is enabled
+    fn mark_unsaved(&mut self) {
+        self.unsaved_changes = true;
+        if self.autosave {
+            self.save_heroes_file();
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(heroes) = self.undo_stack.undo() {
+            self.heroes = heroes;
+            self.mark_unsaved();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(heroes) = self.undo_stack.redo() {
+            self.heroes = heroes;
+            self.mark_unsaved();
+        }
+    }
+
+    /// Reload the heroes and history files if another program changed them. Unsaved changes to the
+    /// heroes aren't discarded without asking.
+    fn check_external_changes(&mut self, ctx: &egui::Context) {
+        ctx.request_repaint_after(WATCH_INTERVAL);
+
+        let history_path = History::path_for(&self.file_path);
+        if self.history_watcher.poll(&history_path) {
+            self.load_history();
+        }
+
+        if !self.heroes_watcher.poll(&self.file_path) {
+            return;
+        }
+        // Our own saves change the file too, but leave it matching what's in memory
+        let on_disk = std::fs::read_to_string(&self.file_path).ok();
+        let in_memory = Format::from_path(&self.file_path).serialize(&self.heroes);
+        if on_disk.is_some() && on_disk == in_memory.ok() {
+            return;
+        }
+        println!("Heroes file '{}' changed on disk", self.file_path.display());
+        if self.load_error.is_some() || !self.unsaved_changes {
+            self.load_heroes_file();
+        } else {
+            self.file_changed = true;
+        }
+    }
+
+    /// Combine the heroes file on disk with the heroes in memory and save the result
+    fn merge_heroes_file(&mut self) {
+        self.file_changed = false;
+        match load_heroes(&self.file_path) {
+            Ok(heroes) => {
+                self.heroes.merge(&heroes);
+                self.heroes_changed();
+            }
+            Err(err) => {
+                println!(
+                    "Unable to load heroes from '{}': {err}",
+                    self.file_path.display()
+                );
+                self.load_error = Some(LoadErrorDialog::new(&self.file_path, err));
+            }
+        }
+    }
+
+    /// Show the error of `result`, if any, returning whether it succeeded
+    fn report<E: Display>(&mut self, result: Result<(), E>) -> bool {
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                println!("{err}");
+                self.error_message = Some(err.to_string());
+                false
+            }
+        }
+    }
+
+    /// Keep the default heroes shown after the heroes file failed to load, so they are saved over it.
+    /// The unreadable file is backed up first, as the regular backups may not have a copy of it.
+    fn use_default_heroes(&mut self) {
+        if self.file_path.exists() {
+            match create_backup(&self.file_path, "corrupt") {
+                Ok(backup) => {
+                    println!("Backed up unreadable heroes file to '{}'", backup.display())
+                }
+                Err(err) => {
+                    self.report(Err(format!(
+                        "Unable to back up '{}', keeping it: {err}",
+                        self.file_path.display()
+                    )));
+                    return;
+                }
+            }
+        }
+        self.load_error = None;
+        self.unsaved_changes = true;
+    }
+
+    /// Like [`PickMeApp::report`], keeping the value on success
+    fn report_value<T, E: Display>(&mut self, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.report(Err(err));
+                None
+            }
+        }
+    }
+
+    /// Replace the heroes file with `backup`, after saving any changes so they are backed up too
+    fn restore_heroes_backup(&mut self, backup: &Path) {
+        println!("Restoring heroes file from '{}'", backup.display());
+        self.save_heroes_file();
+        if self.report(restore_backup(&self.file_path, backup)) {
+            self.load_heroes_file();
+        }
+    }
+
+    /// Copy the file path and filters in use back into the active profile
+    fn store_profile(&mut self) {
+        let profile = &mut self.profiles[self.active_profile];
+        profile.file_path = self.file_path.clone();
+        profile.filters = self.filters.clone();
+    }
+
+    /// Load the heroes, history and filters of the active profile
+    fn load_profile(&mut self) {
+        let profile = &self.profiles[self.active_profile];
+        println!("Switching to profile '{}'", profile.name);
+        self.file_path = profile.file_path.clone();
+        self.filters = profile.filters.clone();
+        self.remember_file();
+        self.load_heroes_file();
+        self.load_history();
+        self.picker.clear_session();
+        self.picked = None;
+    }
+
+    /// Store the active profile and save its heroes before leaving it, returning whether that
+    /// succeeded. Unsaved changes are never dropped, so the profile can't be left if they can't be saved.
+    fn leave_profile(&mut self) -> bool {
+        self.store_profile();
+        self.remember_file();
+        if self.unsaved_changes && !self.save_heroes_file() {
+            self.error_message = Some(format!(
+                "Unable to save the changes to '{}', staying on profile '{}'",
+                self.file_path.display(),
+                self.profiles[self.active_profile].name
+            ));
+            return false;
+        }
+        true
+    }
+
+    fn switch_profile(&mut self, index: usize) {
+        if self.leave_profile() {
+            self.active_profile = index;
+            self.load_profile();
+        }
+    }
+
+    /// Add `profile` and switch to it
+    fn add_profile(&mut self, profile: Profile) {
+        self.profiles.push(profile);
+        self.active_profile = self.profiles.len() - 1;
+        self.load_profile();
+    }
+
+    fn apply_profile_action(&mut self, action: ProfileAction, name: String) {
+        match action {
+            ProfileAction::Create => {
+                let file_path = Profile::new_file_path(&name, &self.file_path, &self.profiles);
+                let Some(file_path) = self.report_value(file_path) else {
+                    return;
+                };
+                if self.leave_profile() {
+                    self.add_profile(Profile::new(name, file_path, Filters::default()));
+                }
+            }
+            ProfileAction::Rename => self.profiles[self.active_profile].name = name,
+            ProfileAction::Duplicate => {
+                let file_path = Profile::new_file_path(&name, &self.file_path, &self.profiles);
+                let Some(file_path) = self.report_value(file_path) else {
+                    return;
+                };
+                if !self.leave_profile() {
+                    return;
+                }
+                println!("Copying heroes into new file: {}", file_path.display());
+                if self.report(save_heroes(&file_path, &self.heroes)) {
+                    self.add_profile(Profile::new(name, file_path, self.filters.clone()));
+                }
+            }
+            ProfileAction::Delete => {
+                if self.profiles.len() > 1 && self.leave_profile() {
+                    self.profiles.remove(self.active_profile);
+                    self.active_profile = 0;
+                    self.load_profile();
                 }
-            } else {
-                println!("Could not open heroes file, loading defaults");
-                Heroes::default()
             }
+        }
+    }
+
+    fn open_file_dialog(&mut self) {
+        let file_path = heroes_file_dialog().pick_file();
+        if let Some(file_path) = file_path {
+            self.open_file(file_path);
         } else {
-            println!("Heroes file does not exist, loading defaults");
-            Heroes::default()
+            println!("No file selected");
         }
     }
 
-    fn lowest_level(&self) -> u32 {
-        let heroes = self.apply_filters();
-        heroes
-            .iter()
-            .fold(u32::MAX, |acc, hero| hero.level.min(acc))
+    fn open_file(&mut self, file_path: PathBuf) {
+        println!("Setting new file path: {}", file_path.display());
+        self.change_file(file_path);
+        self.load_history();
+        self.load_heroes_file();
+    }
+
+    /// Use `file_path` from now on, restoring the filters last used with it
+    fn change_file(&mut self, file_path: PathBuf) {
+        self.remember_file();
+        if let Some(filters) = self.recent_files.filters(&file_path) {
+            self.filters = filters.clone();
+        }
+        self.file_path = file_path;
+        self.remember_file();
     }
 
-    fn draw_hero_row(ui: &mut Ui, hero: &mut Hero, selected: bool) {
+    /// Add the current file to the recent files, along with the filters in use
+    fn remember_file(&mut self) {
+        self.recent_files.remember(&self.file_path, &self.filters);
+    }
+
+    fn import_csv_dialog(&mut self) {
+        let Some(path) = csv_file_dialog().pick_file() else {
+            println!("No file selected");
+            return;
+        };
+        match import_csv(&path, &mut self.heroes) {
+            Ok(count) => {
+                println!("Imported {count} heroes from '{}'", path.display());
+                self.heroes_changed();
+            }
+            Err(err) => {
+                self.report(Err(err));
+            }
+        }
+    }
+
+    fn export_csv_dialog(&mut self) {
+        let file_name = self
+            .file_path
+            .with_extension("csv")
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some(path) = csv_file_dialog().set_file_name(file_name).save_file() else {
+            println!("No file selected");
+            return;
+        };
+        println!("Exporting heroes to '{}'", path.display());
+        self.report(export_csv(&path, &self.heroes));
+    }
+
+    /// Load the history file for `file_path`. If that fails, an empty history is shown and the file
+    /// isn't saved until it can be read again
+    fn load_history(&mut self) {
+        let path = History::path_for(&self.file_path);
+        match History::load(&path) {
+            Ok(history) => {
+                self.history = history;
+                self.history_load_failed = false;
+            }
+            Err(err) => {
+                self.history = History::default();
+                self.history_load_failed = true;
+                self.report(Err(format!(
+                    "Unable to load history from '{}', new picks aren't saved until it is fixed: {err}",
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    fn save_history(&mut self) {
+        let path = History::path_for(&self.file_path);
+        if self.history_load_failed {
+            println!(
+                "Not saving history over unreadable file '{}'",
+                path.display()
+            );
+            return;
+        }
+        let result = self
+            .history
+            .save(&path)
+            .map_err(|err| format!("Unable to save history to '{}': {err}", path.display()));
+        self.report(result);
+    }
+
+    fn load_file_path(storage: &dyn eframe::Storage) -> PathBuf {
+        if let Some(file_path) = storage.get_string(KEY_FILE_PATH) {
+            println!("Retrieved file path from storage: {file_path}");
+            PathBuf::from(file_path)
+        } else {
+            println!("Loading from default file path");
+            PathBuf::from_str(DEFAULT_FILE_PATH).unwrap()
+        }
+    }
+
+    /// Draw the row for `hero`, reporting whether it was changed and whether its edit dialog was requested
+    fn draw_hero_row(
+        ui: &mut Ui,
+        hero: &mut Hero,
+        mode: Option<GameMode>,
+        selected: bool,
+        known_tags: &[String],
+    ) -> RowResponse {
+        let mut changed = false;
+        let mut edit = None;
         ui.horizontal(|ui| {
             if ui.button("⬆").clicked() {
-                hero.level_up();
+                hero.level_up_in(mode);
+                changed = true;
+            }
+            if ui.button("⬇").clicked() {
+                hero.level_down_in(mode);
+                changed = true;
             }
             let star = if hero.favourite { "★" } else { "☆" };
             if ui
@@ -150,192 +666,719 @@ impl PickMeApp {
                 .clicked()
             {
                 hero.toggle_favourite();
+                changed = true;
             }
             if ui
                 .button(RichText::new("🚫").color(Color32::LIGHT_RED))
                 .clicked()
             {
                 hero.toggle_blacklisted();
+                changed = true;
+            }
+            let mut level = hero.level_in(mode);
+            let minimum = if mode.is_some() { 0 } else { 1 };
+            let response = ui.add(egui::DragValue::new(&mut level).range(minimum..=u32::MAX));
+            if level != hero.level_in(mode) {
+                hero.set_level_in(mode, level);
+            }
+            // Only count a drag as an edit once it ends, rather than on every frame
+            if (response.changed() && !response.dragged()) || response.drag_stopped() {
+                changed = true;
             }
-            if hero.blacklisted {
-                ui.label(RichText::new(hero.to_string()).strikethrough());
+            let text = RichText::new(&hero.name);
+            let text = if hero.blacklisted {
+                text.strikethrough()
             } else if selected {
-                ui.label(RichText::new(hero.to_string()).strong());
+                text.strong()
             } else {
-                ui.label(RichText::new(hero.to_string()));
+                text
+            };
+            let mut label = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+            if !hero.sub_roles.is_empty() {
+                let sub_roles: Vec<String> =
+                    hero.sub_roles.iter().map(SubRole::to_string).collect();
+                label = label.on_hover_text(sub_roles.join(", "));
             }
+            label.context_menu(|ui| {
+                if ui.button("Edit…").clicked() {
+                    edit = Some((hero.name.clone(), false));
+                }
+                if ui.button("Delete…").clicked() {
+                    edit = Some((hero.name.clone(), true));
+                }
+            });
+            for tag in &hero.tags {
+                ui.label(RichText::new(format!("#{tag}")).small().weak());
+            }
+            ui.menu_button("🏷", |ui| {
+                changed |= Self::draw_tag_menu(ui, hero, known_tags);
+            })
+            .response
+            .on_hover_text("Edit tags");
         });
+        RowResponse { changed, edit }
+    }
+
+    /// Menu to remove tags from `hero` or add new or `known_tags`, returns whether `hero` was changed
+    fn draw_tag_menu(ui: &mut Ui, hero: &mut Hero, known_tags: &[String]) -> bool {
+        let mut changed = false;
+        for tag in hero.tags.clone() {
+            if ui
+                .button(format!("✖ {tag}"))
+                .on_hover_text("Remove tag")
+                .clicked()
+            {
+                changed |= hero.remove_tag(&tag);
+            }
+        }
+        let unused: Vec<&String> = known_tags
+            .iter()
+            .filter(|tag| !hero.tags.contains(*tag))
+            .collect();
+        if !unused.is_empty() {
+            ui.separator();
+            for tag in unused {
+                if ui.button(format!("+ {tag}")).clicked() {
+                    changed |= hero.add_tag(tag);
+                }
+            }
+        }
+        ui.separator();
+        let id = ui.make_persistent_id(("new_tag", &hero.name));
+        let mut new_tag: String = ui.data_mut(|data| data.get_temp(id)).unwrap_or_default();
+        let response = ui.add(egui::TextEdit::singleline(&mut new_tag).hint_text("New tag"));
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            changed |= hero.add_tag(&new_tag);
+            new_tag.clear();
+        }
+        ui.data_mut(|data| data.insert_temp(id, new_tag));
+        changed
     }
 
+    /// Buttons cycling each tag between required, excluded and ignored
+    fn draw_tag_filters(ui: &mut Ui, filters: &mut Filters, known_tags: &[String]) {
+        let mut tags: Vec<String> = known_tags.to_vec();
+        tags.extend(filters.include_tags.iter().cloned());
+        tags.extend(filters.exclude_tags.iter().cloned());
+        tags.sort_unstable();
+        tags.dedup();
+        if tags.is_empty() {
+            return;
+        }
+        ui.label("Tags:");
+        for tag in tags {
+            let included = filters.include_tags.contains(&tag);
+            let excluded = filters.exclude_tags.contains(&tag);
+            let text = if included {
+                RichText::new(format!("+{tag}")).color(Color32::LIGHT_GREEN)
+            } else if excluded {
+                RichText::new(format!("-{tag}"))
+                    .color(Color32::LIGHT_RED)
+                    .strikethrough()
+            } else {
+                RichText::new(&tag)
+            };
+            if ui
+                .selectable_label(included || excluded, text)
+                .on_hover_text("Click to require, exclude or ignore heroes with this tag")
+                .clicked()
+            {
+                if included {
+                    filters.include_tags.remove(&tag);
+                    filters.exclude_tags.insert(tag);
+                } else if excluded {
+                    filters.exclude_tags.remove(&tag);
+                } else {
+                    filters.include_tags.insert(tag);
+                }
+            }
+        }
+    }
+
+    /// Draw the column of `heroes` with `role`, reporting whether any were changed and whether an
+    /// edit dialog was requested
     fn draw_role_column(
         ui: &mut Ui,
         heroes: &mut Vec<Hero>,
         role: Role,
         filters: &Filters,
         lowest_level: u32,
-        session_picked: &[Hero],
-    ) {
+        picker: &Picker,
+        known_tags: &[String],
+    ) -> RowResponse {
+        let mut response = RowResponse::default();
         ui.vertical(|ui| {
             ui.heading(role.to_string());
             for hero in heroes {
-                Self::draw_hero_row(
+                let selected = picker.is_selected(hero, filters, lowest_level);
+                response.merge(Self::draw_hero_row(
                     ui,
                     hero,
-                    (!filters.lowest || lowest_level == hero.level)
-                        && filters.is_selected(hero, role, session_picked),
-                );
+                    filters.mode,
+                    selected,
+                    known_tags,
+                ));
             }
         });
+        response
     }
 
-    fn apply_filters(&self) -> Vec<Hero> {
-        let mut all_heroes: Vec<Hero> = Vec::new();
-        let mut tanks = self.heroes.tanks.clone();
-        tanks.retain(|hero| {
-            self.filters
-                .is_selected(hero, Role::Tank, &self.session_picked)
-        });
-        let mut damages = self.heroes.damages.clone();
-        damages.retain(|hero| {
-            self.filters
-                .is_selected(hero, Role::Damage, &self.session_picked)
+    /// Open the edit dialog for the hero called `name`, or straight away ask to delete it
+    fn open_hero_edit_dialog(&mut self, name: &str, delete: bool) {
+        let in_roster = Roster::active(&self.file_path)
+            .heroes
+            .iter()
+            .any(|entry| entry.name == name);
+        self.hero_edit_dialog = HeroEditDialog::new(&self.heroes, name, in_roster).map(|dialog| {
+            if delete {
+                dialog.confirm_delete()
+            } else {
+                dialog
+            }
         });
-        let mut supports = self.heroes.supports.clone();
-        supports.retain(|hero| {
-            self.filters
-                .is_selected(hero, Role::Support, &self.session_picked)
+    }
+
+    fn apply_hero_edit(&mut self, edit: HeroEdit) {
+        let unchanged = self.heroes.iter().any(|hero| {
+            hero.name == edit.name
+                && hero.name == edit.new_name
+                && hero.role == edit.role
+                && hero.stadium == edit.stadium
         });
-        all_heroes.append(&mut tanks);
-        all_heroes.append(&mut damages);
-        all_heroes.append(&mut supports);
-        let lowest = all_heroes
+        if unchanged {
+            return;
+        }
+        let renamed = self.heroes.rename_hero(&edit.name, &edit.new_name);
+        if !self.report(renamed) {
+            return;
+        }
+        let result = self.heroes.move_hero(&edit.new_name, edit.role);
+        if let Some(hero) = self.heroes.find_mut(&edit.new_name) {
+            hero.stadium = edit.stadium;
+        }
+        self.report(result);
+        self.heroes_changed();
+    }
+
+    /// Start the picks over from the seed, or from entropy if there is none
+    fn reseed(&mut self) {
+        self.rng = self
+            .seed
+            .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        self.picker.clear_session();
+    }
+
+    fn draw_seed(&mut self, ui: &mut Ui) {
+        let mut reseed = false;
+        // Laid out right to left
+        if let Some(seed) = &mut self.seed {
+            reseed |= ui
+                .button("⟲")
+                .on_hover_text("Restart the picks from the seed")
+                .clicked();
+            reseed |= ui.add(egui::DragValue::new(seed)).changed();
+        }
+        let mut seeded = self.seed.is_some();
+        if ui
+            .checkbox(&mut seeded, "Seed")
+            .on_hover_text("Make the picks and teams reproducible by starting from a fixed seed")
+            .changed()
+        {
+            self.seed = seeded.then_some(0);
+            reseed = true;
+        }
+        if reseed {
+            self.reseed();
+        }
+    }
+
+    fn apply_preset(&mut self, index: usize) {
+        if let Some(preset) = self.filter_presets.get(index) {
+            if self.filters.unique && !preset.filters.unique {
+                self.picker.clear_session();
+            }
+            self.filters = preset.filters.clone();
+        }
+    }
+
+    /// Save the current filters as a preset called `name`, replacing any preset with that name
+    fn save_preset(&mut self, name: String) {
+        let filters = self.filters.clone();
+        match self
+            .filter_presets
+            .iter_mut()
+            .find(|preset| preset.name == name)
+        {
+            Some(preset) => preset.filters = filters,
+            None => self.filter_presets.push(FilterPreset { name, filters }),
+        }
+    }
+
+    /// Index of the preset with the same filters as the current ones
+    fn active_preset(&self) -> Option<usize> {
+        self.filter_presets
             .iter()
-            .fold(u32::MAX, |acc, hero| acc.min(hero.level));
-        all_heroes.retain(|hero| hero.level == lowest || !self.filters.lowest);
-        all_heroes
+            .position(|preset| preset.filters == self.filters)
+    }
+
+    fn draw_presets(&mut self, ui: &mut Ui) {
+        ui.label("Preset:");
+        let active = self.active_preset();
+        let mut apply = None;
+        egui::ComboBox::from_id_salt("filter_preset")
+            .selected_text(active.map_or("Custom", |index| &self.filter_presets[index].name))
+            .show_ui(ui, |ui| {
+                for (index, preset) in self.filter_presets.iter().enumerate() {
+                    let mut button = egui::Button::selectable(active == Some(index), &preset.name);
+                    if let Some(shortcut) = preset_shortcut(index) {
+                        button = button.shortcut_text(ui.ctx().format_shortcut(&shortcut));
+                    }
+                    if ui.add(button).clicked() {
+                        apply = Some(index);
+                    }
+                }
+            });
+        if let Some(index) = apply {
+            self.apply_preset(index);
+        }
+        if ui.button("Save as preset…").clicked() {
+            self.preset_dialog = Some(PresetDialog::default());
+        }
+        if let Some(index) = active {
+            if ui.button("🗑").on_hover_text("Delete this preset").clicked() {
+                self.filter_presets.remove(index);
+            }
+        }
     }
 }
 
 impl eframe::App for PickMeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let lowest_level = self.lowest_level();
+        self.check_external_changes(ctx);
+        if ctx.input_mut(|i| i.consume_shortcut(&SAVE_SHORTCUT)) {
+            self.save_heroes_file();
+        }
+        // Leave undo and redo to text fields while they are being edited
+        if !ctx.wants_keyboard_input() {
+            // Check the shortcut with shift first, as the one without it also matches
+            if ctx.input_mut(|i| {
+                i.consume_shortcut(&REDO_SHORTCUT_ALT) || i.consume_shortcut(&REDO_SHORTCUT)
+            }) {
+                self.redo();
+            } else if ctx.input_mut(|i| i.consume_shortcut(&UNDO_SHORTCUT)) {
+                self.undo();
+            }
+            let preset = (0..self.filter_presets.len()).find(|index| {
+                preset_shortcut(*index)
+                    .is_some_and(|shortcut| ctx.input_mut(|i| i.consume_shortcut(&shortcut)))
+            });
+            if let Some(index) = preset {
+                self.apply_preset(index);
+            }
+        }
+        if ctx.input(|i| i.viewport().close_requested()) && !self.exiting && self.unsaved_changes {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.pending_action = Some(PendingAction::Exit);
+        }
+        let lowest_level = self.picker.lowest_level(&self.heroes, &self.filters);
+        let known_tags: Vec<String> = self.heroes.tags().into_iter().map(String::from).collect();
         egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
                 ui.menu_button("File", |ui| {
                     if ui.button("New").clicked() {
-                        let file_path = FileDialog::new()
-                            .set_directory("./")
-                            .set_file_name("heroes.yaml")
-                            .add_filter("YAML", &["yaml"])
-                            .save_file();
-                        if let Some(file_path) = file_path {
-                            println!("Creating new heroes file: {}", file_path.to_str().unwrap());
-                            self.file_path = file_path;
-                            self.heroes = Heroes::default();
-                        }
+                        self.request_action(ctx, PendingAction::New);
                     }
                     if ui.button("Open..").clicked() {
-                        let file_path = FileDialog::new()
-                            .add_filter("YAML", &["yaml"])
-                            .set_directory("./")
-                            .pick_file();
-                        if let Some(file_path) = file_path {
-                            println!("Setting new file path: {}", file_path.to_str().unwrap());
-                            self.heroes = Self::load_heroes(&file_path);
-                            self.file_path = file_path;
-                        } else {
-                            println!("No file selected");
+                        self.request_action(ctx, PendingAction::Open);
+                    }
+                    ui.menu_button("Open Recent", |ui| {
+                        if self.recent_files.is_empty() {
+                            ui.label("No recent files");
+                        }
+                        let mut open = None;
+                        for file in self.recent_files.iter() {
+                            let name = file
+                                .file_path
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_default();
+                            let response = ui
+                                .add_enabled(file.file_path.exists(), egui::Button::new(name))
+                                .on_hover_text(file.file_path.display().to_string())
+                                .on_disabled_hover_text("File not found");
+                            if response.clicked() {
+                                open = Some(file.file_path.clone());
+                            }
+                        }
+                        if let Some(file_path) = open {
+                            self.request_action(ctx, PendingAction::OpenFile(file_path));
                         }
+                        if !self.recent_files.is_empty() {
+                            ui.separator();
+                            if ui.button("Clear").clicked() {
+                                self.recent_files.clear();
+                                self.remember_file();
+                            }
+                        }
+                    });
+                    if ui
+                        .add(
+                            egui::Button::new("Save")
+                                .shortcut_text(ctx.format_shortcut(&SAVE_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.save_heroes_file();
                     }
                     if ui.button("Save As..").clicked() {
-                        let file_path = FileDialog::new()
-                            .set_directory("./")
-                            .set_file_name("heroes.yaml")
-                            .add_filter("YAML", &["yaml"])
-                            .save_file();
-                        if let Some(file_path) = file_path {
-                            println!(
-                                "Saving heroes into new file: {}",
-                                file_path.to_str().unwrap()
-                            );
-                            self.file_path = file_path;
+                        self.save_as_dialog();
+                    }
+                    ui.menu_button("Restore backup…", |ui| {
+                        let backups = list_backups(&self.file_path);
+                        if backups.is_empty() {
+                            ui.label("No backups");
+                        }
+                        for backup in backups {
+                            let name = backup.file_name().unwrap().to_string_lossy().into_owned();
+                            if ui.button(name).clicked() {
+                                self.restore_heroes_backup(&backup);
+                            }
                         }
+                    });
+                    ui.separator();
+                    if ui.button("Import CSV..").clicked() {
+                        self.import_csv_dialog();
+                    }
+                    if ui.button("Export CSV..").clicked() {
+                        self.export_csv_dialog();
+                    }
+                    ui.separator();
+                    if ui.checkbox(&mut self.autosave, "Autosave").changed()
+                        && self.autosave
+                        && self.unsaved_changes
+                    {
+                        self.save_heroes_file();
+                    }
+                });
+                ui.menu_button("Edit", |ui| {
+                    if ui
+                        .add_enabled(
+                            self.undo_stack.can_undo(),
+                            egui::Button::new("Undo")
+                                .shortcut_text(ctx.format_shortcut(&UNDO_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.undo();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.undo_stack.can_redo(),
+                            egui::Button::new("Redo")
+                                .shortcut_text(ctx.format_shortcut(&REDO_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.redo();
                     }
                 });
                 ui.menu_button("Hero", |ui| {
                     if ui.button("New").clicked() {
                         self.show_add_hero_dialog = true
                     }
-                })
+                    if ui.button("Reset all levels").clicked() {
+                        self.heroes.reset_levels();
+                        self.heroes_changed();
+                    }
+                });
+                ui.menu_button("Profile", |ui| {
+                    if ui.button("New..").clicked() {
+                        self.profile_dialog =
+                            Some(ProfileDialog::new(ProfileAction::Create, String::new()));
+                    }
+                    let name = self.profiles[self.active_profile].name.clone();
+                    if ui.button("Rename..").clicked() {
+                        self.profile_dialog =
+                            Some(ProfileDialog::new(ProfileAction::Rename, name.clone()));
+                    }
+                    if ui.button("Duplicate..").clicked() {
+                        self.profile_dialog = Some(ProfileDialog::new(
+                            ProfileAction::Duplicate,
+                            format!("{name} copy"),
+                        ));
+                    }
+                    if ui
+                        .add_enabled(self.profiles.len() > 1, egui::Button::new("Delete"))
+                        .clicked()
+                    {
+                        self.profile_dialog = Some(ProfileDialog::new(ProfileAction::Delete, name));
+                    }
+                });
+                ui.menu_button("View", |ui| {
+                    ui.checkbox(&mut self.history_window.open, "History");
+                    ui.checkbox(&mut self.team_window.open, "Team");
+                });
+                ui.separator();
+                let mut selected = self.active_profile;
+                egui::ComboBox::from_id_salt("profile")
+                    .selected_text(&self.profiles[self.active_profile].name)
+                    .show_ui(ui, |ui| {
+                        for (index, profile) in self.profiles.iter().enumerate() {
+                            ui.selectable_value(&mut selected, index, &profile.name);
+                        }
+                    });
+                if selected != self.active_profile {
+                    self.switch_profile(selected);
+                }
             })
         });
+        if let Some(error_message) = &self.error_message {
+            let mut dismissed = false;
+            egui::TopBottomPanel::bottom("error").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::LIGHT_RED, error_message);
+                    dismissed = ui.button("✖").clicked();
+                });
+            });
+            if dismissed {
+                self.error_message = None;
+            }
+        }
+        let mut rows = RowResponse::default();
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
                     if ui.button("Pick Me").clicked() {
-                        let mut all_heroes = self.apply_filters();
-
-                        if all_heroes.is_empty() && !self.session_picked.is_empty() {
-                            self.session_picked.clear();
-                            all_heroes = self.apply_filters();
-                        }
-
-                        if let Some(hero) = all_heroes.choose(&mut rand::thread_rng()) {
-                            self.picked = Some(hero.name.clone());
-                            if self.filters.unique {
-                                self.session_picked.push(hero.clone());
-                            }
-                        } else {
-                            self.picked = Some("Empty selection".to_string());
+                        let pick =
+                            self.picker
+                                .pick_with(&self.heroes, &self.filters, &mut self.rng);
+                        if let Pick::Hero(hero) = &pick {
+                            self.history.record(hero, &self.filters);
+                            self.save_history();
                         }
+                        self.picked = Some(pick);
                     }
-                    if let Some(hero) = &self.picked {
-                        ui.heading(hero);
+                    if let Some(pick) = &self.picked {
+                        ui.heading(pick.to_string());
                     } else {
                         ui.heading("<- Click me");
                     }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        self.draw_seed(ui)
+                    });
                 });
+                ui.horizontal(|ui| self.draw_presets(ui));
                 ui.horizontal(|ui| {
-                    ui.checkbox(&mut self.filters.tank, "Tank");
-                    ui.checkbox(&mut self.filters.damage, "Damage");
-                    ui.checkbox(&mut self.filters.support, "Support");
-                    ui.checkbox(&mut self.filters.favourite, "Favourite");
+                    ui.label("Mode:");
+                    let mode_name = |mode: Option<GameMode>| {
+                        mode.map_or("All modes".to_string(), |mode| mode.to_string())
+                    };
+                    egui::ComboBox::from_id_salt("mode")
+                        .selected_text(mode_name(self.filters.mode))
+                        .show_ui(ui, |ui| {
+                            for mode in [None].into_iter().chain(GameMode::ALL.map(Some)) {
+                                ui.selectable_value(&mut self.filters.mode, mode, mode_name(mode));
+                            }
+                        });
+                    ui.separator();
+                    // Replaced by the expression while one is set
+                    let checkboxes = self.filters.expression.is_none();
+                    ui.add_enabled(
+                        checkboxes,
+                        egui::Checkbox::new(&mut self.filters.tank, "Tank"),
+                    );
+                    ui.add_enabled(
+                        checkboxes,
+                        egui::Checkbox::new(&mut self.filters.damage, "Damage"),
+                    );
+                    ui.add_enabled(
+                        checkboxes,
+                        egui::Checkbox::new(&mut self.filters.support, "Support"),
+                    );
+                    ui.add_enabled(
+                        checkboxes,
+                        egui::Checkbox::new(&mut self.filters.favourite, "Favourite"),
+                    );
                     ui.checkbox(&mut self.filters.lowest, "Lowest");
                     if ui.checkbox(&mut self.filters.unique, "Unique").clicked()
                         && !self.filters.unique
                     {
-                        self.session_picked = Vec::new();
+                        self.picker.clear_session();
+                    }
+                    ui.add_enabled(
+                        checkboxes,
+                        egui::Checkbox::new(&mut self.filters.stadium, "Stadium"),
+                    );
+                    ui.checkbox(&mut self.filters.weighted, "Weighted");
+                    if self.filters.weighted {
+                        egui::ComboBox::from_id_salt("weight_curve")
+                            .selected_text(self.filters.weights.curve.to_string())
+                            .show_ui(ui, |ui| {
+                                for curve in WeightCurve::ALL {
+                                    ui.selectable_value(
+                                        &mut self.filters.weights.curve,
+                                        curve,
+                                        curve.to_string(),
+                                    );
+                                }
+                            });
+                        ui.label("Favourite ×");
+                        ui.add(
+                            egui::DragValue::new(&mut self.filters.weights.favourite_multiplier)
+                                .range(0.0..=10.0)
+                                .speed(0.1),
+                        );
                     }
-                    ui.checkbox(&mut self.filters.stadium, "Stadium");
                 });
+                let checkboxes = self.filters.expression.is_none();
+                ui.horizontal(|ui| {
+                    if !checkboxes {
+                        ui.disable();
+                    }
+                    ui.label("Sub-roles:");
+                    for sub_role in SubRole::ALL {
+                        let mut checked = self.filters.sub_roles.contains(&sub_role);
+                        if ui.checkbox(&mut checked, sub_role.to_string()).changed() {
+                            if checked {
+                                self.filters.sub_roles.insert(sub_role);
+                            } else {
+                                self.filters.sub_roles.remove(&sub_role);
+                            }
+                        }
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    if !checkboxes {
+                        ui.disable();
+                    }
+                    Self::draw_tag_filters(ui, &mut self.filters, &known_tags);
+                });
+                self.expression_edit.show(ui, &mut self.filters);
             });
             ui.horizontal(|ui| {
-                Self::draw_role_column(
+                rows.merge(Self::draw_role_column(
                     ui,
                     self.heroes.tanks.as_mut(),
                     Role::Tank,
                     &self.filters,
                     lowest_level,
-                    &self.session_picked,
-                );
-                Self::draw_role_column(
+                    &self.picker,
+                    &known_tags,
+                ));
+                rows.merge(Self::draw_role_column(
                     ui,
                     self.heroes.damages.as_mut(),
                     Role::Damage,
                     &self.filters,
                     lowest_level,
-                    &self.session_picked,
-                );
-                Self::draw_role_column(
+                    &self.picker,
+                    &known_tags,
+                ));
+                rows.merge(Self::draw_role_column(
                     ui,
                     self.heroes.supports.as_mut(),
                     Role::Support,
                     &self.filters,
                     lowest_level,
-                    &self.session_picked,
-                );
+                    &self.picker,
+                    &known_tags,
+                ));
             })
         });
+        if rows.changed {
+            self.heroes_changed();
+        }
+        if let Some((name, delete)) = rows.edit {
+            self.open_hero_edit_dialog(&name, delete);
+        }
+
+        if let Some(dialog) = &mut self.hero_edit_dialog {
+            match dialog.show(ctx, &self.heroes) {
+                HeroEditResponse::Open => {}
+                HeroEditResponse::Cancel => self.hero_edit_dialog = None,
+                HeroEditResponse::Save(edit) => {
+                    self.hero_edit_dialog = None;
+                    self.apply_hero_edit(edit);
+                }
+                HeroEditResponse::Delete(name) => {
+                    self.hero_edit_dialog = None;
+                    if self.heroes.remove(&name).is_some() {
+                        println!("Deleted hero '{name}'");
+                        self.heroes_changed();
+                    }
+                }
+            }
+        }
+
+        if self.history_window.show(ctx, &mut self.history) {
+            self.save_history();
+        }
+
+        self.team_window
+            .show(ctx, &self.file_path, &self.heroes, &mut self.rng);
+
+        if let Some(dialog) = &mut self.load_error {
+            match dialog.show(ctx, &self.file_path) {
+                LoadErrorResponse::Open => {}
+                LoadErrorResponse::Retry => self.load_heroes_file(),
+                LoadErrorResponse::OpenOther => self.open_file_dialog(),
+                LoadErrorResponse::Restore(backup) => self.restore_heroes_backup(&backup),
+                LoadErrorResponse::UseDefaults => self.use_default_heroes(),
+            }
+        }
+
+        if self.file_changed {
+            match show_file_changed_dialog(ctx, &self.file_path) {
+                FileChangedResponse::Open => {}
+                FileChangedResponse::Reload => self.load_heroes_file(),
+                FileChangedResponse::Overwrite => {
+                    self.file_changed = false;
+                    self.save_heroes_file();
+                }
+                FileChangedResponse::Merge => self.merge_heroes_file(),
+            }
+        }
+
+        if let Some(action) = self.pending_action.clone() {
+            match show_unsaved_changes_dialog(ctx, &self.file_path) {
+                UnsavedChangesResponse::Open => {}
+                UnsavedChangesResponse::Save => {
+                    self.pending_action = None;
+                    if self.save_heroes_file() {
+                        self.run_action(ctx, action);
+                    }
+                }
+                UnsavedChangesResponse::Discard => {
+                    self.pending_action = None;
+                    self.run_action(ctx, action);
+                }
+                UnsavedChangesResponse::Cancel => self.pending_action = None,
+            }
+        }
+
+        self.update_title(ctx);
+
+        if let Some(dialog) = &mut self.preset_dialog {
+            match dialog.show(ctx, &self.filter_presets) {
+                PresetResponse::Open => {}
+                PresetResponse::Cancel => self.preset_dialog = None,
+                PresetResponse::Save(name) => {
+                    self.preset_dialog = None;
+                    self.save_preset(name);
+                }
+            }
+        }
+
+        if let Some(dialog) = &mut self.profile_dialog {
+            match dialog.show(ctx, &self.profiles, &self.file_path) {
+                ProfileResponse::Open => {}
+                ProfileResponse::Cancel => self.profile_dialog = None,
+                ProfileResponse::Confirm(action, name) => {
+                    self.profile_dialog = None;
+                    self.apply_profile_action(action, name);
+                }
+            }
+        }
 
         if self.show_add_hero_dialog {
             ctx.show_viewport_immediate(
@@ -345,10 +1388,13 @@ impl eframe::App for PickMeApp {
                     .with_inner_size([220.0, 75.0]), /* .with_resizable(false)*/
                 |ctx, class| {
                     assert!(
-                        class == egui::ViewportClass::Immediate,
-                        "This egui backend doesn't support multiple viewports"
+                        matches!(
+                            class,
+                            egui::ViewportClass::Immediate | egui::ViewportClass::Embedded
+                        ),
+                        "This egui backend doesn't support immediate viewports"
                     );
-                    egui::CentralPanel::default().show(ctx, |ui| {
+                    let add_hero_form = |ui: &mut Ui| {
                         egui::Grid::new("add_hero_grid").show(ui, |ui| {
                             ui.label("Role:");
                             egui::ComboBox::from_id_salt("role")
@@ -372,52 +1418,45 @@ impl eframe::App for PickMeApp {
                                 });
                             ui.end_row();
 
-                            ui.label("Hero name:");
-                            ui.text_edit_singleline(&mut self.hero_name);
+                            let label = ui.label("Hero name:");
+                            ui.text_edit_singleline(&mut self.hero_name)
+                                .labelled_by(label.id);
                             ui.end_row();
 
-                            if ui.button("Add").clicked() && !self.hero_name.is_empty() {
-                                match self.role {
-                                    Role::Tank => {
-                                        self.heroes.tanks.push(Hero::new(
-                                            &self.hero_name,
-                                            Role::Tank,
-                                            false,
-                                        ));
-                                        self.heroes
-                                            .tanks
-                                            .sort_unstable_by_key(|hero| hero.name.clone());
-                                    }
-                                    Role::Damage => {
-                                        self.heroes.damages.push(Hero::new(
-                                            &self.hero_name,
-                                            Role::Damage,
-                                            false,
-                                        ));
-                                        self.heroes
-                                            .damages
-                                            .sort_unstable_by_key(|hero| hero.name.clone());
-                                    }
-                                    Role::Support => {
-                                        self.heroes.supports.push(Hero::new(
-                                            &self.hero_name,
-                                            Role::Support,
-                                            false,
-                                        ));
-                                        self.heroes
-                                            .supports
-                                            .sort_unstable_by_key(|hero| hero.name.clone());
-                                    }
-                                }
+                            let check = self.heroes.check_new_name(&self.hero_name);
+                            if ui
+                                .add_enabled(check.is_ok(), egui::Button::new("Add"))
+                                .clicked()
+                            {
+                                self.heroes
+                                    .add(Hero::new(self.hero_name.trim(), self.role, false));
+                                self.hero_name.clear();
                                 self.show_add_hero_dialog = false;
+                                self.heroes_changed();
+                            }
+                            if let Err(err @ EditError::NameTaken(_)) = check {
+                                ui.colored_label(Color32::LIGHT_RED, err.to_string());
                             }
                             ui.end_row();
                         });
-                    });
+                    };
 
-                    if ctx.input(|i| i.viewport().close_requested()) {
-                        // Tell parent viewport that we should not show next frame:
-                        self.show_add_hero_dialog = false;
+                    if class == egui::ViewportClass::Embedded {
+                        // Backends without multiple viewports, such as the test harness, draw the
+                        // dialog as a window in the main viewport
+                        let mut open = true;
+                        egui::Window::new("Add hero")
+                            .open(&mut open)
+                            .collapsible(false)
+                            .resizable(false)
+                            .show(ctx, add_hero_form);
+                        self.show_add_hero_dialog &= open;
+                    } else {
+                        egui::CentralPanel::default().show(ctx, add_hero_form);
+                        if ctx.input(|i| i.viewport().close_requested()) {
+                            // Tell parent viewport that we should not show next frame:
+                            self.show_add_hero_dialog = false;
+                        }
                     }
                 },
             )
@@ -425,16 +1464,216 @@ impl eframe::App for PickMeApp {
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        if let Some(parent_dir) = self.file_path.parent() {
-            std::fs::create_dir_all(parent_dir).expect("Unable to create parent directory");
+        self.store_profile();
+        save_profiles(storage, &self.profiles, self.active_profile);
+        storage.set_string(KEY_AUTOSAVE, self.autosave.to_string());
+        self.remember_file();
+        self.recent_files.save(storage);
+        storage.set_string(
+            KEY_FILTER_PRESETS,
+            serde_json::to_string(&self.filter_presets)
+                .expect("Unable to serialize filter presets"),
+        );
+        storage.set_string(
+            KEY_TEAM,
+            serde_json::to_string(&self.team_window.settings)
+                .expect("Unable to serialize team settings"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use eframe::Storage as _;
+    use egui::accesskit::Role as AccessRole;
+    use egui_kittest::{kittest::Queryable, Harness};
+
+    use super::*;
+
+    /// eframe storage kept in memory
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
         }
 
-        let heroes_file = File::create(&self.file_path).expect("Unable to open 'heroes.yaml'");
-        serde_yaml::to_writer(heroes_file, &self.heroes).expect("Unable to save heroes to file");
+        fn flush(&mut self) {}
+    }
+
+    /// Empty directory for the heroes and history files of one test, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("pickme-gui-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// App using a heroes file in `dir`, which doesn't exist yet
+    fn harness(dir: &TempDir) -> Harness<'static, PickMeApp> {
+        let mut storage = MemoryStorage::default();
         storage.set_string(
-            KEY_FILTERS,
-            serde_json::to_string(&self.filters).expect("Unable to serialize filters"),
+            KEY_FILE_PATH,
+            dir.0.join("heroes.yaml").to_string_lossy().into_owned(),
+        );
+        let mut harness = Harness::builder()
+            .with_size([1400.0, 900.0])
+            .build_eframe(|_| PickMeApp::with_storage(&storage));
+        harness.run();
+        harness
+    }
+
+    fn picked_hero(harness: &Harness<'_, PickMeApp>) -> Hero {
+        match &harness.state().picked {
+            Some(Pick::Hero(hero)) => hero.clone(),
+            pick => panic!("expected a hero to be picked, got {pick:?}"),
+        }
+    }
+
+    #[test]
+    fn pick_me_shows_picked_hero() {
+        let dir = TempDir::new("pick");
+        let mut harness = harness(&dir);
+        harness.get_by_label("<- Click me");
+
+        harness.get_by_label("Pick Me").click();
+        harness.run();
+
+        let hero = picked_hero(&harness);
+        assert!(harness.query_by_label("<- Click me").is_none());
+        // The hero's row and the heading
+        assert_eq!(harness.get_all_by_label(&hero.name).count(), 2);
+        assert_eq!(harness.state().history.entries.len(), 1);
+    }
+
+    #[test]
+    fn role_checkboxes_filter_picks() {
+        let dir = TempDir::new("filters");
+        let mut harness = harness(&dir);
+
+        for role in ["Tank", "Damage"] {
+            harness
+                .get_by_role_and_label(AccessRole::CheckBox, role)
+                .click();
+        }
+        harness.run();
+        let filters = &harness.state().filters;
+        assert!(!filters.tank && !filters.damage && filters.support);
+
+        for _ in 0..10 {
+            harness.get_by_label("Pick Me").click();
+            harness.run();
+            assert_eq!(picked_hero(&harness).role, Role::Support);
+        }
+
+        harness
+            .get_by_role_and_label(AccessRole::CheckBox, "Support")
+            .click();
+        harness.run();
+        harness.get_by_label("Pick Me").click();
+        harness.run();
+        assert_eq!(harness.state().picked, Some(Pick::Empty));
+        harness.get_by_label("Empty selection");
+    }
+
+    #[test]
+    fn add_hero_dialog_adds_hero() {
+        let dir = TempDir::new("add-hero");
+        let mut harness = harness(&dir);
+
+        harness.get_by_label("Hero").click();
+        harness.run();
+        harness.get_by_label("New").click();
+        harness.run();
+        assert!(harness.state().show_add_hero_dialog);
+
+        let name_input = harness.get_by_role_and_label(AccessRole::TextInput, "Hero name:");
+        name_input.focus();
+        name_input.type_text("Test Hero");
+        harness.run();
+        assert_eq!(harness.state().hero_name, "Test Hero");
+        harness.get_by_label("Add").click();
+        harness.run();
+
+        let state = harness.state();
+        assert!(!state.show_add_hero_dialog);
+        assert!(state
+            .heroes
+            .tanks
+            .iter()
+            .any(|hero| hero.name == "Test Hero"));
+        // Autosave is on by default
+        let saved = std::fs::read_to_string(dir.0.join("heroes.yaml")).unwrap();
+        assert!(saved.contains("Test Hero"));
+        harness.get_by_label("Test Hero");
+    }
+
+    #[test]
+    fn add_hero_dialog_rejects_taken_name() {
+        let dir = TempDir::new("add-taken");
+        let mut harness = harness(&dir);
+        harness.state_mut().show_add_hero_dialog = true;
+        harness.run();
+
+        let name_input = harness.get_by_role_and_label(AccessRole::TextInput, "Hero name:");
+        name_input.focus();
+        name_input.type_text("reinhardt");
+        harness.run();
+        harness.get_by_label("A hero named 'reinhardt' already exists");
+        harness.get_by_label("Add").click();
+        harness.run();
+
+        let state = harness.state();
+        assert!(state.show_add_hero_dialog);
+        assert_eq!(
+            state
+                .heroes
+                .iter()
+                .filter(|hero| hero.name == "Reinhardt")
+                .count(),
+            1
         );
-        storage.set_string(KEY_FILE_PATH, self.file_path.to_str().unwrap().to_string());
+    }
+
+    #[test]
+    fn removed_profiles_are_cleared_from_storage() {
+        let mut storage = MemoryStorage::default();
+        let profile = |name: &str| {
+            Profile::new(
+                name,
+                PathBuf::from(format!("{name}.yaml")),
+                Filters::default(),
+            )
+        };
+        save_profiles(&mut storage, &[profile("a"), profile("b")], 0);
+        assert_eq!(storage.get_string("profile/b/file_path").unwrap(), "b.yaml");
+
+        save_profiles(&mut storage, &[profile("a"), profile("c")], 1);
+        assert_eq!(storage.get_string("profile/b/file_path").unwrap(), "");
+        assert_eq!(storage.get_string("profile/b/filters").unwrap(), "");
+        let (profiles, active) = load_profiles(&storage).unwrap();
+        let names: Vec<&str> = profiles
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+        assert_eq!((names, active), (vec!["a", "c"], 1));
     }
 }
@@ -0,0 +1,309 @@
+use std::fmt::Display;
+
+use rand::{prelude::SliceRandom, Rng};
+
+use crate::{Filters, Hero, Heroes};
+
+/// Result of asking the [`Picker`] for a hero
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pick {
+    Hero(Hero),
+    /// No hero matched the filters
+    Empty,
+}
+
+impl Display for Pick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pick::Hero(hero) => write!(f, "{}", hero.name),
+            Pick::Empty => write!(f, "Empty selection"),
+        }
+    }
+}
+
+/// Picks random heroes, keeping track of the heroes already picked this session for the `unique` filter
+#[derive(Clone, Debug, Default)]
+pub struct Picker {
+    session_picked: Vec<Hero>,
+}
+
+impl Picker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn session_picked(&self) -> &[Hero] {
+        &self.session_picked
+    }
+
+    pub fn clear_session(&mut self) {
+        self.session_picked.clear();
+    }
+
+    /// All heroes passing `filters`, including the `lowest` filter
+    pub fn apply_filters(&self, heroes: &Heroes, filters: &Filters) -> Vec<Hero> {
+        let mut all_heroes: Vec<Hero> = heroes
+            .iter()
+            .filter(|hero| filters.is_selected(hero, &self.session_picked))
+            .cloned()
+            .collect();
+        let lowest = all_heroes
+            .iter()
+            .fold(u32::MAX, |acc, hero| acc.min(filters.level(hero)));
+        all_heroes.retain(|hero| filters.level(hero) == lowest || !filters.lowest);
+        all_heroes
+    }
+
+    /// Lowest level among the heroes passing `filters`, or `u32::MAX` if there are none
+    pub fn lowest_level(&self, heroes: &Heroes, filters: &Filters) -> u32 {
+        self.apply_filters(heroes, filters)
+            .iter()
+            .fold(u32::MAX, |acc, hero| filters.level(hero).min(acc))
+    }
+
+    /// Whether `hero` would be part of the selection, given the `lowest_level` of the selection
+    pub fn is_selected(&self, hero: &Hero, filters: &Filters, lowest_level: u32) -> bool {
+        (!filters.lowest || lowest_level == filters.level(hero))
+            && filters.is_selected(hero, &self.session_picked)
+    }
+
+    /// Pick a random hero passing `filters`, see [`Picker::pick_with`]
+    pub fn pick(&mut self, heroes: &Heroes, filters: &Filters) -> Pick {
+        self.pick_with(heroes, filters, &mut rand::thread_rng())
+    }
+
+    /// Pick a random hero passing `filters` using `rng`, so picks can be reproduced from a seed.
+    ///
+    /// If the `unique` filter has exhausted every hero, the session is reset and the pick retried.
+    /// Weighted picks fall back to a uniform pick if every hero has a weight of zero.
+    pub fn pick_with<R: Rng>(&mut self, heroes: &Heroes, filters: &Filters, rng: &mut R) -> Pick {
+        let mut all_heroes = self.apply_filters(heroes, filters);
+
+        if all_heroes.is_empty() && !self.session_picked.is_empty() {
+            self.session_picked.clear();
+            all_heroes = self.apply_filters(heroes, filters);
+        }
+
+        let hero = if filters.weighted {
+            all_heroes
+                .choose_weighted(rng, |hero| filters.weight(hero))
+                .ok()
+                .or_else(|| all_heroes.choose(rng))
+        } else {
+            all_heroes.choose(rng)
+        };
+
+        if let Some(hero) = hero {
+            if filters.unique {
+                self.session_picked.push(hero.clone());
+            }
+            Pick::Hero(hero.clone())
+        } else {
+            Pick::Empty
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{GameMode, Role};
+
+    const PICKS: usize = 6000;
+
+    fn heroes() -> Heroes {
+        let mut heroes = Heroes {
+            version: 0,
+            tanks: Vec::new(),
+            damages: Vec::new(),
+            supports: Vec::new(),
+            archived: Vec::new(),
+        };
+        for (name, role, level) in [
+            ("Tank 1", Role::Tank, 1),
+            ("Tank 2", Role::Tank, 4),
+            ("Damage 1", Role::Damage, 1),
+            ("Damage 2", Role::Damage, 1),
+            ("Damage 3", Role::Damage, 9),
+            ("Support 1", Role::Support, 2),
+        ] {
+            let mut hero = Hero::new(name, role, false);
+            hero.level = level;
+            heroes.add(hero);
+        }
+        heroes
+    }
+
+    /// How many times each hero was picked in `count` picks from `seed`
+    fn pick_counts(filters: &Filters, seed: u64, count: usize) -> HashMap<String, usize> {
+        let heroes = heroes();
+        let mut picker = Picker::new();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut counts = HashMap::new();
+        for _ in 0..count {
+            match picker.pick_with(&heroes, filters, &mut rng) {
+                Pick::Hero(hero) => *counts.entry(hero.name).or_default() += 1,
+                Pick::Empty => panic!("no hero picked"),
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn same_seed_gives_same_picks() {
+        let heroes = heroes();
+        let filters = Filters::default();
+        let picks = |seed| {
+            let mut picker = Picker::new();
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..20)
+                .map(|_| picker.pick_with(&heroes, &filters, &mut rng))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(picks(1), picks(1));
+        assert_ne!(picks(1), picks(2));
+    }
+
+    #[test]
+    fn uniform_picks_cover_filtered_heroes_evenly() {
+        let filters = Filters {
+            tank: false,
+            ..Filters::default()
+        };
+        let counts = pick_counts(&filters, 7, PICKS);
+        let expected = PICKS / 4;
+        assert_eq!(counts.len(), 4);
+        for (name, count) in &counts {
+            assert!(!name.starts_with("Tank"), "{name} doesn't pass the filters");
+            assert!(
+                count.abs_diff(expected) < expected / 10,
+                "{name} picked {count} times, expected about {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn lowest_only_picks_lowest_level_heroes() {
+        let filters = Filters {
+            lowest: true,
+            ..Filters::default()
+        };
+        let heroes = heroes();
+        let selected = Picker::new().apply_filters(&heroes, &filters);
+        let names: Vec<&str> = selected.iter().map(|hero| hero.name.as_str()).collect();
+        assert_eq!(names, ["Tank 1", "Damage 1", "Damage 2"]);
+
+        let counts = pick_counts(&filters, 3, PICKS);
+        assert_eq!(counts.len(), 3);
+        assert!(counts.keys().all(|name| names.contains(&name.as_str())));
+    }
+
+    #[test]
+    fn lowest_ignores_heroes_filtered_out() {
+        let filters = Filters {
+            lowest: true,
+            tank: false,
+            damage: false,
+            ..Filters::default()
+        };
+        let heroes = heroes();
+        let picker = Picker::new();
+        let selected = picker.apply_filters(&heroes, &filters);
+        assert_eq!(selected, [heroes.supports[0].clone()]);
+        assert_eq!(picker.lowest_level(&heroes, &filters), 2);
+    }
+
+    #[test]
+    fn lowest_uses_levels_in_mode() {
+        let filters = Filters {
+            lowest: true,
+            mode: Some(GameMode::Competitive),
+            ..Filters::default()
+        };
+        let mut heroes = heroes();
+        for hero in heroes.iter_mut() {
+            hero.set_level_in(Some(GameMode::Competitive), 3);
+        }
+        heroes
+            .find_mut("Damage 3")
+            .unwrap()
+            .set_level_in(Some(GameMode::Competitive), 2);
+        let selected = Picker::new().apply_filters(&heroes, &filters);
+        let names: Vec<&str> = selected.iter().map(|hero| hero.name.as_str()).collect();
+        assert_eq!(names, ["Damage 3"]);
+    }
+
+    #[test]
+    fn weighted_picks_favour_lower_levels() {
+        let filters = Filters {
+            weighted: true,
+            ..Filters::default()
+        };
+        let counts = pick_counts(&filters, 11, PICKS);
+        let count = |name: &str| counts.get(name).copied().unwrap_or_default();
+        assert!(count("Damage 1") > count("Support 1"));
+        assert!(count("Support 1") > count("Tank 2"));
+        assert!(count("Tank 2") > count("Damage 3"));
+    }
+
+    #[test]
+    fn unique_picks_every_hero_before_repeating() {
+        let filters = Filters {
+            unique: true,
+            ..Filters::default()
+        };
+        let heroes = heroes();
+        let mut picker = Picker::new();
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..3 {
+            let mut names: Vec<String> = (0..heroes.iter().count())
+                .map(|_| match picker.pick_with(&heroes, &filters, &mut rng) {
+                    Pick::Hero(hero) => hero.name,
+                    Pick::Empty => panic!("no hero picked"),
+                })
+                .collect();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), heroes.iter().count());
+        }
+    }
+
+    #[test]
+    fn unique_exhaustion_resets_session() {
+        let filters = Filters {
+            unique: true,
+            tank: false,
+            ..Filters::default()
+        };
+        let heroes = heroes();
+        let mut picker = Picker::new();
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..4 {
+            picker.pick_with(&heroes, &filters, &mut rng);
+        }
+        assert_eq!(picker.session_picked().len(), 4);
+        assert!(picker.apply_filters(&heroes, &filters).is_empty());
+
+        let Pick::Hero(hero) = picker.pick_with(&heroes, &filters, &mut rng) else {
+            panic!("no hero picked after the session was reset");
+        };
+        assert_eq!(picker.session_picked(), [hero]);
+    }
+
+    #[test]
+    fn no_matching_hero_is_empty() {
+        let filters = Filters {
+            favourite: true,
+            ..Filters::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Picker::new().pick_with(&heroes(), &filters, &mut rng),
+            Pick::Empty
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{schema, Hero, Heroes, Role, SubRole};
+
+/// File name of a user supplied roster, looked up next to the heroes file
+pub const ROSTER_FILE_NAME: &str = "roster.yaml";
+
+const EMBEDDED_ROSTER: &str = include_str!("../assets/roster.yaml");
+
+/// Canonical information about a hero released by Blizzard
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RosterEntry {
+    pub name: String,
+    pub role: Role,
+    #[serde(default)]
+    pub stadium: bool,
+    #[serde(default)]
+    pub sub_roles: Vec<SubRole>,
+    #[serde(default)]
+    pub release_date: Option<NaiveDate>,
+}
+
+impl RosterEntry {
+    pub fn hero(&self) -> Hero {
+        let mut hero = Hero::new(&self.name, self.role, self.stadium);
+        hero.sub_roles = self.sub_roles.iter().copied().collect();
+        hero
+    }
+}
+
+/// Every hero in the game, used to build new heroes files and migrate old ones
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Roster {
+    pub heroes: Vec<RosterEntry>,
+    /// Heroes that have been renamed, from old name to new name
+    #[serde(default)]
+    pub renames: BTreeMap<String, String>,
+    /// Heroes that have been removed from the game
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+impl Roster {
+    /// Roster shipped with the application
+    ///
+    /// # Panics
+    /// Panics if the embedded roster is invalid
+    pub fn embedded() -> Self {
+        static ROSTER: OnceLock<Roster> = OnceLock::new();
+        ROSTER
+            .get_or_init(|| {
+                serde_yaml::from_str(EMBEDDED_ROSTER).expect("Unable to parse embedded roster")
+            })
+            .clone()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        serde_yaml::from_reader(file).map_err(io::Error::other)
+    }
+
+    /// Path of the user supplied roster overriding the embedded one for the heroes file at `heroes_path`
+    pub fn path_for(heroes_path: &Path) -> PathBuf {
+        heroes_path.with_file_name(ROSTER_FILE_NAME)
+    }
+
+    /// Roster in effect for the heroes file at `heroes_path`: the user supplied roster next to it if
+    /// there is one, otherwise the embedded roster
+    pub fn active(heroes_path: &Path) -> Self {
+        let path = Self::path_for(heroes_path);
+        if !path.exists() {
+            return Self::embedded();
+        }
+        match Self::load(&path) {
+            Ok(roster) => {
+                eprintln!("Using roster from '{}'", path.display());
+                roster
+            }
+            Err(err) => {
+                eprintln!(
+                    "Could not load roster from '{}', using built-in roster: {err}",
+                    path.display()
+                );
+                Self::embedded()
+            }
+        }
+    }
+
+    /// Fresh heroes, one per roster entry
+    pub fn heroes(&self) -> Heroes {
+        let mut heroes = Heroes {
+            version: schema::CURRENT_VERSION,
+            tanks: Vec::new(),
+            damages: Vec::new(),
+            supports: Vec::new(),
+            archived: Vec::new(),
+        };
+        for entry in &self.heroes {
+            heroes.add(entry.hero());
+        }
+        heroes
+    }
+}
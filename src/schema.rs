@@ -0,0 +1,85 @@
+//! Versioned layout of heroes files and the steps to upgrade old files to the current version
+
+use std::{error::Error, fmt::Display};
+
+use serde_yaml::{Mapping, Value};
+
+/// Version written to heroes files by this build
+pub const CURRENT_VERSION: u32 = 3;
+
+/// Upgrade from the version at the same index to the next version
+const STEPS: [fn(&mut Mapping); CURRENT_VERSION as usize] = [v0_to_v1, v1_to_v2, v2_to_v3];
+
+#[derive(Debug, PartialEq)]
+pub enum SchemaError {
+    /// The document isn't a mapping
+    NotAMapping,
+    /// The file was written by a newer version of the application
+    UnsupportedVersion(u32),
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::NotAMapping => write!(f, "Heroes file is not a mapping"),
+            SchemaError::UnsupportedVersion(version) => write!(
+                f,
+                "Heroes file version {version} is newer than the supported version {CURRENT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl Error for SchemaError {}
+
+/// Version of a heroes document, files without a version are version 0
+pub fn version(document: &Value) -> u32 {
+    document
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| version as u32)
+}
+
+/// Apply every step needed to bring `document` up to [`CURRENT_VERSION`]
+pub fn upgrade(document: &mut Value) -> Result<(), SchemaError> {
+    let from = version(document);
+    if from > CURRENT_VERSION {
+        return Err(SchemaError::UnsupportedVersion(from));
+    }
+    let mapping = document.as_mapping_mut().ok_or(SchemaError::NotAMapping)?;
+    for (version, step) in STEPS.iter().enumerate().skip(from as usize) {
+        eprintln!(
+            "Upgrading heroes file from version {version} to {}",
+            version + 1
+        );
+        step(mapping);
+        mapping.insert("version".into(), (version as u32 + 1).into());
+    }
+    Ok(())
+}
+
+/// Every hero mapping in the role lists of `mapping`
+fn heroes_mut(mapping: &mut Mapping) -> impl Iterator<Item = &mut Mapping> {
+    mapping
+        .iter_mut()
+        .filter(|(key, _)| matches!(key.as_str(), Some("tanks" | "damages" | "supports")))
+        .filter_map(|(_, heroes)| heroes.as_sequence_mut())
+        .flatten()
+        .filter_map(Value::as_mapping_mut)
+}
+
+/// Version 1 introduced the version field itself and writes `blacklisted` for every hero. The field
+/// still defaults to `false` when reading, so hand-written files can leave it out.
+fn v0_to_v1(mapping: &mut Mapping) {
+    for hero in heroes_mut(mapping) {
+        if !hero.contains_key("blacklisted") {
+            hero.insert("blacklisted".into(), false.into());
+        }
+    }
+}
+
+/// Version 2 added per-mode levels, which start empty. Older versions would drop them when saving.
+fn v1_to_v2(_mapping: &mut Mapping) {}
+
+/// Version 3 added user tags, which start empty
+fn v2_to_v3(_mapping: &mut Mapping) {}
@@ -0,0 +1,128 @@
+//! Flat table of heroes for round-tripping progress with spreadsheets as CSV
+
+use std::{error::Error, fmt::Display, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Hero, Heroes, Role};
+
+/// One hero in a CSV file
+#[derive(Deserialize, Serialize)]
+struct Row {
+    name: String,
+    role: String,
+    level: u32,
+    favourite: bool,
+    blacklisted: bool,
+    stadium: bool,
+}
+
+impl From<&Hero> for Row {
+    fn from(hero: &Hero) -> Self {
+        Self {
+            name: hero.name.clone(),
+            role: hero.role.to_string(),
+            level: hero.level,
+            favourite: hero.favourite,
+            blacklisted: hero.blacklisted,
+            stadium: hero.stadium,
+        }
+    }
+}
+
+/// Error importing or exporting a CSV file
+#[derive(Debug)]
+pub enum CsvError {
+    Csv(csv::Error),
+    /// A row has a value that isn't valid for its column
+    Row {
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvError::Csv(err) => write!(f, "Invalid CSV file: {err}"),
+            CsvError::Row { line, message } => {
+                write!(f, "Invalid CSV file on line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Csv(err) => Some(err),
+            CsvError::Row { .. } => None,
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(err: csv::Error) -> Self {
+        CsvError::Csv(err)
+    }
+}
+
+/// Write every hero to `path` as CSV with a header row
+pub fn export_csv(path: &Path, heroes: &Heroes) -> Result<(), CsvError> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for hero in heroes.iter() {
+        writer.serialize(Row::from(hero))?;
+    }
+    writer.flush().map_err(csv::Error::from)?;
+    Ok(())
+}
+
+/// Update `heroes` from the CSV file at `path`, returning the number of heroes imported.
+///
+/// Heroes are matched by name, ignoring case, and moved if their role changed. Heroes that don't
+/// exist yet are added. Nothing is changed if any row is invalid.
+pub fn import_csv(path: &Path, heroes: &mut Heroes) -> Result<usize, CsvError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut rows = Vec::new();
+    for (index, row) in reader.deserialize::<Row>().enumerate() {
+        let mut row = row?;
+        // Spreadsheets often keep stray spaces around names
+        row.name = row.name.trim().to_string();
+        // The header is the first line
+        let line = index + 2;
+        let role: Role = row
+            .role
+            .parse()
+            .map_err(|message| CsvError::Row { line, message })?;
+        if row.name.is_empty() {
+            return Err(CsvError::Row {
+                line,
+                message: "hero name is empty".to_string(),
+            });
+        }
+        if row.level < 1 {
+            return Err(CsvError::Row {
+                line,
+                message: "level must be at least 1".to_string(),
+            });
+        }
+        rows.push((row, role));
+    }
+
+    let count = rows.len();
+    for (row, role) in rows {
+        let mut hero = heroes
+            .remove(&row.name)
+            .unwrap_or_else(|| Hero::new(row.name, role, row.stadium));
+        if hero.role != role {
+            hero.sub_roles.clear();
+        }
+        hero.role = role;
+        hero.level = row.level;
+        hero.favourite = row.favourite;
+        hero.blacklisted = row.blacklisted;
+        hero.stadium = row.stadium;
+        heroes.add(hero);
+    }
+    Ok(count)
+}
@@ -0,0 +1,479 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+    str::FromStr,
+};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{Filters, Hero, Heroes, Role};
+
+/// Number of heroes of each role in a team
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Composition {
+    pub tanks: usize,
+    pub damages: usize,
+    pub supports: usize,
+}
+
+impl Composition {
+    /// 1 tank, 2 damage, 2 support
+    pub const ROLE_QUEUE_5V5: Composition = Composition {
+        tanks: 1,
+        damages: 2,
+        supports: 2,
+    };
+    /// 2 tank, 2 damage, 2 support
+    pub const ROLE_QUEUE_6V6: Composition = Composition {
+        tanks: 2,
+        damages: 2,
+        supports: 2,
+    };
+
+    pub fn size(&self) -> usize {
+        self.tanks + self.damages + self.supports
+    }
+
+    pub fn count(&self, role: Role) -> usize {
+        match role {
+            Role::Tank => self.tanks,
+            Role::Damage => self.damages,
+            Role::Support => self.supports,
+        }
+    }
+}
+
+impl Default for Composition {
+    fn default() -> Self {
+        Self::ROLE_QUEUE_5V5
+    }
+}
+
+impl FromStr for Composition {
+    type Err = String;
+
+    /// Parse `5v5`, `6v6` or a `tanks-damages-supports` triple such as `1-2-2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "5v5" => return Ok(Self::ROLE_QUEUE_5V5),
+            "6v6" => return Ok(Self::ROLE_QUEUE_6V6),
+            _ => {}
+        }
+        let counts = s
+            .split('-')
+            .map(|count| count.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("invalid composition '{s}'"))?;
+        match counts[..] {
+            [tanks, damages, supports] => Ok(Self {
+                tanks,
+                damages,
+                supports,
+            }),
+            _ => Err(format!("invalid composition '{s}'")),
+        }
+    }
+}
+
+impl Display for Composition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.tanks, self.damages, self.supports)
+    }
+}
+
+/// A member of the team with their own heroes and filters
+#[derive(Clone, Debug)]
+pub struct Player {
+    pub name: String,
+    pub heroes: Heroes,
+    /// Filters applied to the player's heroes. The role filters are ignored, as roles come from the composition
+    pub filters: Filters,
+}
+
+/// Hero assigned to a player
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assignment {
+    pub player: String,
+    pub role: Role,
+    pub hero: Hero,
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({})", self.player, self.hero.name, self.role)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TeamError {
+    /// There are more players than slots in the composition
+    TooManyPlayers { players: usize, slots: usize },
+    /// No way to give every player a distinct hero matching their filters
+    NoValidAssignment,
+}
+
+impl Display for TeamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TeamError::TooManyPlayers { players, slots } => write!(
+                f,
+                "{players} players don't fit in a composition with {slots} slots"
+            ),
+            TeamError::NoValidAssignment => {
+                write!(f, "No way to give every player a distinct hero")
+            }
+        }
+    }
+}
+
+impl Error for TeamError {}
+
+/// Assign every player a role from `composition` and a distinct hero of that role.
+///
+/// Assignments are returned in the same order as `players`.
+pub fn generate_team(
+    players: &[Player],
+    composition: Composition,
+) -> Result<Vec<Assignment>, TeamError> {
+    generate_team_with(players, composition, &mut rand::thread_rng())
+}
+
+/// [`generate_team`] using `rng`, so teams can be reproduced from a seed
+pub fn generate_team_with<R: Rng>(
+    players: &[Player],
+    composition: Composition,
+    rng: &mut R,
+) -> Result<Vec<Assignment>, TeamError> {
+    if players.len() > composition.size() {
+        return Err(TeamError::TooManyPlayers {
+            players: players.len(),
+            slots: composition.size(),
+        });
+    }
+
+    let mut order: Vec<usize> = (0..players.len()).collect();
+    order.shuffle(rng);
+
+    let mut team = TeamBuilder {
+        players,
+        remaining: composition,
+        taken: HashSet::new(),
+        assignments: vec![None; players.len()],
+        failed: HashSet::new(),
+    };
+    if team.can_complete(&order) && team.assign(&order, rng) {
+        Ok(team.assignments.into_iter().flatten().collect())
+    } else {
+        Err(TeamError::NoValidAssignment)
+    }
+}
+
+/// Players left to assign, open slots and taken heroes (sorted) of a search state
+type SearchState = (usize, [usize; 3], Vec<String>);
+
+/// Backtracking search state for [`generate_team`]
+struct TeamBuilder<'a> {
+    players: &'a [Player],
+    remaining: Composition,
+    taken: HashSet<String>,
+    assignments: Vec<Option<Assignment>>,
+    /// States already known to have no valid assignment
+    failed: HashSet<SearchState>,
+}
+
+impl TeamBuilder<'_> {
+    /// Assign the players in `order`, returning whether everyone got a hero
+    fn assign(&mut self, order: &[usize], rng: &mut impl Rng) -> bool {
+        let Some((&index, rest)) = order.split_first() else {
+            return true;
+        };
+        let state = self.state(order);
+        if self.failed.contains(&state) {
+            return false;
+        }
+        let player = &self.players[index];
+
+        let mut roles = Role::ALL.to_vec();
+        roles.shuffle(rng);
+        for role in roles {
+            if self.remaining.count(role) == 0 {
+                continue;
+            }
+            for hero in self.candidates(player, role, rng) {
+                self.take(role, &hero.name);
+                self.assignments[index] = Some(Assignment {
+                    player: player.name.clone(),
+                    role,
+                    hero: hero.clone(),
+                });
+                if self.can_complete(rest) && self.assign(rest, rng) {
+                    return true;
+                }
+                self.assignments[index] = None;
+                self.release(role, &hero.name);
+            }
+        }
+        self.failed.insert(state);
+        false
+    }
+
+    fn state(&self, order: &[usize]) -> SearchState {
+        let mut taken: Vec<String> = self.taken.iter().cloned().collect();
+        taken.sort_unstable();
+        let remaining = &self.remaining;
+        (
+            order.len(),
+            [remaining.tanks, remaining.damages, remaining.supports],
+            taken,
+        )
+    }
+
+    /// Whether the players in `order` could still each get an open slot and a distinct hero,
+    /// found with a maximum flow from players through heroes to role slots.
+    ///
+    /// The "lowest" filter is ignored here, so this can only rule states out. It lets the search
+    /// fail fast on impossible setups instead of trying every combination.
+    fn can_complete(&self, order: &[usize]) -> bool {
+        const SOURCE: usize = 0;
+        const SINK: usize = 1;
+        let role_node = |role: Role| 2 + Role::ALL.iter().position(|r| *r == role).unwrap();
+        let player_node = |position: usize| 2 + Role::ALL.len() + position;
+
+        // Keyed by hero name and role node
+        let mut heroes: HashMap<(&str, usize), usize> = HashMap::new();
+        let mut edges = Vec::new();
+        for (position, &index) in order.iter().enumerate() {
+            let player = &self.players[index];
+            edges.push((SOURCE, player_node(position), 1));
+            for role in Role::ALL {
+                if self.remaining.count(role) == 0 {
+                    continue;
+                }
+                for hero in self.eligible(player, role) {
+                    let next = player_node(order.len()) + heroes.len();
+                    let node = *heroes
+                        .entry((hero.name.as_str(), role_node(role)))
+                        .or_insert(next);
+                    edges.push((player_node(position), node, 1));
+                }
+            }
+        }
+        for (&(_, role), &node) in &heroes {
+            edges.push((node, role, 1));
+        }
+        for role in Role::ALL {
+            edges.push((role_node(role), SINK, self.remaining.count(role)));
+        }
+
+        let nodes = player_node(order.len()) + heroes.len();
+        let mut capacity = vec![vec![0; nodes]; nodes];
+        for (from, to, amount) in edges {
+            capacity[from][to] += amount;
+        }
+        max_flow(&mut capacity, SOURCE, SINK) == order.len()
+    }
+
+    fn remaining_mut(&mut self, role: Role) -> &mut usize {
+        match role {
+            Role::Tank => &mut self.remaining.tanks,
+            Role::Damage => &mut self.remaining.damages,
+            Role::Support => &mut self.remaining.supports,
+        }
+    }
+
+    fn take(&mut self, role: Role, hero: &str) {
+        *self.remaining_mut(role) -= 1;
+        self.taken.insert(hero.to_string());
+    }
+
+    fn release(&mut self, role: Role, hero: &str) {
+        *self.remaining_mut(role) += 1;
+        self.taken.remove(hero);
+    }
+
+    /// Filters the player's heroes of `role` are chosen with
+    fn filters(player: &Player, role: Role) -> Filters {
+        Filters {
+            tank: role == Role::Tank,
+            damage: role == Role::Damage,
+            support: role == Role::Support,
+            unique: false,
+            ..player.filters.clone()
+        }
+    }
+
+    /// Heroes of `role` matching the player's filters that aren't taken yet
+    fn eligible<'p>(&self, player: &'p Player, role: Role) -> Vec<&'p Hero> {
+        let filters = Self::filters(player, role);
+        player
+            .heroes
+            .role(role)
+            .iter()
+            .filter(|hero| filters.is_selected(hero, &[]) && !self.taken.contains(&hero.name))
+            .collect()
+    }
+
+    /// Heroes of `role` the player could be given, in the order they should be tried
+    fn candidates<'p>(&self, player: &'p Player, role: Role, rng: &mut impl Rng) -> Vec<&'p Hero> {
+        let filters = Self::filters(player, role);
+        let mut candidates = self.eligible(player, role);
+        if filters.lowest {
+            let lowest = candidates
+                .iter()
+                .fold(u32::MAX, |acc, hero| acc.min(filters.level(hero)));
+            candidates.retain(|hero| filters.level(hero) == lowest);
+        }
+
+        if filters.weighted {
+            // Weighted random order: sort by u^(1/weight) descending
+            let mut keyed: Vec<(f64, &Hero)> = candidates
+                .into_iter()
+                .map(|hero| {
+                    let weight = filters.weight(hero);
+                    let key = if weight > 0.0 {
+                        rng.gen::<f64>().powf(1.0 / weight)
+                    } else {
+                        0.0
+                    };
+                    (key, hero)
+                })
+                .collect();
+            keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+            keyed.into_iter().map(|(_, hero)| hero).collect()
+        } else {
+            candidates.shuffle(rng);
+            candidates
+        }
+    }
+}
+
+/// Maximum flow from `source` to `sink`, leaving the residual capacities in `capacity`
+fn max_flow(capacity: &mut [Vec<usize>], source: usize, sink: usize) -> usize {
+    /// Push one unit along a path from `node` to `sink`, returning whether one was found
+    fn augment(capacity: &mut [Vec<usize>], node: usize, sink: usize, seen: &mut [bool]) -> bool {
+        if node == sink {
+            return true;
+        }
+        seen[node] = true;
+        for next in 0..capacity.len() {
+            if capacity[node][next] > 0 && !seen[next] && augment(capacity, next, sink, seen) {
+                capacity[node][next] -= 1;
+                capacity[next][node] += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut flow = 0;
+    while augment(capacity, source, sink, &mut vec![false; capacity.len()]) {
+        flow += 1;
+    }
+    flow
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn heroes() -> Heroes {
+        let mut heroes = Heroes {
+            version: 0,
+            tanks: Vec::new(),
+            damages: Vec::new(),
+            supports: Vec::new(),
+            archived: Vec::new(),
+        };
+        for role in Role::ALL {
+            for number in 1..=8 {
+                heroes.add(Hero::new(format!("{role} {number}"), role, false));
+            }
+        }
+        heroes
+    }
+
+    fn player(name: &str, heroes: Heroes) -> Player {
+        Player {
+            name: name.to_string(),
+            heroes,
+            filters: Filters::default(),
+        }
+    }
+
+    /// Players who can each only play `hero`
+    fn same_hero_players(count: usize, hero: &str) -> Vec<Player> {
+        (0..count)
+            .map(|number| {
+                let mut heroes = heroes();
+                for other in heroes.iter_mut() {
+                    other.blacklisted = other.name != hero;
+                }
+                player(&format!("Player {number}"), heroes)
+            })
+            .collect()
+    }
+
+    fn assert_fails_quickly(players: &[Player]) {
+        for seed in 0..20 {
+            let start = Instant::now();
+            let team = generate_team_with(
+                players,
+                Composition::ROLE_QUEUE_5V5,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            assert_eq!(team, Err(TeamError::NoValidAssignment));
+            assert!(start.elapsed() < Duration::from_secs(1), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_team() {
+        let players: Vec<Player> = (0..5)
+            .map(|number| player(&format!("Player {number}"), heroes()))
+            .collect();
+        let team = |seed| {
+            generate_team_with(
+                &players,
+                Composition::ROLE_QUEUE_5V5,
+                &mut StdRng::seed_from_u64(seed),
+            )
+            .unwrap()
+        };
+        let assignments = team(3);
+        assert_eq!(assignments, team(3));
+        assert_eq!(assignments.len(), 5);
+        let names: HashSet<&str> = assignments
+            .iter()
+            .map(|assignment| assignment.hero.name.as_str())
+            .collect();
+        assert_eq!(names.len(), 5);
+    }
+
+    #[test]
+    fn player_without_heroes_fails_quickly() {
+        let mut players: Vec<Player> = (0..4)
+            .map(|number| player(&format!("Player {number}"), heroes()))
+            .collect();
+        let mut blacklisted = heroes();
+        for hero in blacklisted.iter_mut() {
+            hero.blacklisted = true;
+        }
+        players.push(player("Blacklisted", blacklisted));
+        assert_fails_quickly(&players);
+    }
+
+    #[test]
+    fn players_sharing_one_hero_fail_quickly() {
+        let mut players: Vec<Player> = (0..3)
+            .map(|number| player(&format!("Player {number}"), heroes()))
+            .collect();
+        players.extend(same_hero_players(2, "Support 1"));
+        assert_fails_quickly(&players);
+    }
+}
@@ -0,0 +1,86 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Hero;
+
+/// How the weight of a hero falls off as its level increases
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum WeightCurve {
+    /// Weight of `1 / level`
+    #[default]
+    Inverse,
+    /// Weight of `1 / level²`
+    InverseSquare,
+    /// Weight halves with every level
+    Exponential,
+}
+
+impl WeightCurve {
+    pub const ALL: [WeightCurve; 3] = [
+        WeightCurve::Inverse,
+        WeightCurve::InverseSquare,
+        WeightCurve::Exponential,
+    ];
+
+    fn weight(&self, level: u32) -> f64 {
+        let level = f64::from(level.max(1));
+        match self {
+            WeightCurve::Inverse => 1.0 / level,
+            WeightCurve::InverseSquare => 1.0 / (level * level),
+            WeightCurve::Exponential => 0.5f64.powf(level - 1.0),
+        }
+    }
+}
+
+impl FromStr for WeightCurve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "inverse" => Ok(WeightCurve::Inverse),
+            "inverse-square" => Ok(WeightCurve::InverseSquare),
+            "exponential" => Ok(WeightCurve::Exponential),
+            _ => Err(format!("unknown weight curve '{s}'")),
+        }
+    }
+}
+
+impl Display for WeightCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightCurve::Inverse => write!(f, "Inverse"),
+            WeightCurve::InverseSquare => write!(f, "Inverse square"),
+            WeightCurve::Exponential => write!(f, "Exponential"),
+        }
+    }
+}
+
+/// Configuration for weighted picks, where lower level heroes are more likely to be picked
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Weights {
+    pub curve: WeightCurve,
+    /// Multiplier applied to the weight of favourite heroes
+    pub favourite_multiplier: f64,
+}
+
+impl Weights {
+    /// Weight of `hero` at `level`, which may be its level in a particular game mode
+    pub fn weight(&self, hero: &Hero, level: u32) -> f64 {
+        let weight = self.curve.weight(level);
+        if hero.favourite {
+            weight * self.favourite_multiplier
+        } else {
+            weight
+        }
+    }
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            curve: WeightCurve::default(),
+            favourite_multiplier: 1.0,
+        }
+    }
+}
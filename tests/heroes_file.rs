@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use pickme::*;
+
+/// Empty directory for one test, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pickme-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn roster() -> Roster {
+    serde_yaml::from_str(
+        "heroes:
+  - name: Reinhardt
+    role: Tank
+    sub_roles: [MainTank]
+  - name: Tracer
+    role: Damage
+  - name: Lucio
+    role: Support
+    stadium: true
+",
+    )
+    .unwrap()
+}
+
+#[test]
+fn yaml_round_trip_keeps_progress() {
+    let dir = TempDir::new("round-trip");
+    let path = dir.0.join("heroes.yaml");
+    let mut heroes = roster().heroes();
+    {
+        let tracer = heroes.find_mut("Tracer").unwrap();
+        tracer.level = 7;
+        tracer.favourite = true;
+        tracer.add_tag("Fun");
+        tracer.set_level_in(Some(GameMode::Competitive), 4);
+    }
+    heroes.find_mut("Lucio").unwrap().blacklisted = true;
+
+    save_heroes(&path, &heroes).unwrap();
+    let loaded = load_heroes_with_roster(&path, &roster()).unwrap();
+
+    assert_eq!(loaded.version, schema::CURRENT_VERSION);
+    assert_eq!(
+        serde_yaml::to_string(&loaded).unwrap(),
+        serde_yaml::to_string(&heroes).unwrap()
+    );
+    let tracer = loaded.iter().find(|hero| hero.name == "Tracer").unwrap();
+    assert_eq!(tracer.level, 7);
+    assert!(tracer.favourite);
+    assert!(tracer.tags.contains("fun"));
+    assert_eq!(tracer.level_in(Some(GameMode::Competitive)), 4);
+    assert!(loaded.supports[0].blacklisted);
+    assert!(loaded.tanks[0].sub_roles.contains(&SubRole::MainTank));
+}
+
+#[test]
+fn old_file_without_blacklisted_loads() {
+    let dir = TempDir::new("old-file");
+    let path = dir.0.join("heroes.yaml");
+    std::fs::write(
+        &path,
+        "tanks:
+  - name: Reinhardt
+    stadium: false
+    role: Tank
+    level: 3
+    favourite: true
+damages: []
+supports: []
+",
+    )
+    .unwrap();
+
+    let heroes = load_heroes_with_roster(&path, &roster()).unwrap();
+
+    assert_eq!(heroes.version, schema::CURRENT_VERSION);
+    let reinhardt = &heroes.tanks[0];
+    assert_eq!(reinhardt.level, 3);
+    assert!(reinhardt.favourite);
+    assert!(!reinhardt.blacklisted);
+    // Heroes missing from the old file are added from the roster
+    assert_eq!(heroes.iter().count(), 3);
+    // The old file is backed up before it is upgraded
+    assert!(dir.0.join("heroes.yaml.v0.bak").exists());
+}
+
+#[test]
+fn missing_file_loads_roster() {
+    let dir = TempDir::new("missing");
+    let heroes = load_heroes_with_roster(&dir.0.join("heroes.yaml"), &roster()).unwrap();
+    let names: Vec<&str> = heroes.iter().map(|hero| hero.name.as_str()).collect();
+    assert_eq!(names, ["Reinhardt", "Tracer", "Lucio"]);
+    assert!(heroes
+        .iter()
+        .all(|hero| hero.level == 1 && !hero.blacklisted));
+}
+
+#[test]
+fn restore_backup_prunes_old_backups() {
+    let dir = TempDir::new("restore");
+    let path = dir.0.join("heroes.yaml");
+    std::fs::write(&path, "current").unwrap();
+    for day in 1..=BACKUP_COUNT {
+        let backup = dir.0.join(format!("heroes.yaml.200001{day:02}-120000.bak"));
+        std::fs::write(backup, format!("day {day}")).unwrap();
+    }
+    let oldest = dir.0.join("heroes.yaml.20000101-120000.bak");
+
+    restore_backup(&path, &oldest).unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "day 1");
+    assert!(!oldest.exists());
+    let backups = list_backups(&path);
+    assert_eq!(backups.len(), BACKUP_COUNT);
+    assert!(backups
+        .iter()
+        .any(|backup| std::fs::read_to_string(backup).unwrap() == "current"));
+}
+
+#[test]
+fn csv_round_trip_keeps_progress() {
+    let dir = TempDir::new("csv-round-trip");
+    let path = dir.0.join("heroes.csv");
+    let mut heroes = roster().heroes();
+    {
+        let tracer = heroes.find_mut("Tracer").unwrap();
+        tracer.level = 7;
+        tracer.favourite = true;
+    }
+    heroes.find_mut("Lucio").unwrap().blacklisted = true;
+
+    export_csv(&path, &heroes).unwrap();
+    let mut imported = roster().heroes();
+    assert_eq!(import_csv(&path, &mut imported).unwrap(), 3);
+
+    assert_eq!(
+        serde_yaml::to_string(&imported).unwrap(),
+        serde_yaml::to_string(&heroes).unwrap()
+    );
+}
+
+#[test]
+fn csv_import_trims_names() {
+    let dir = TempDir::new("csv-trim");
+    let path = dir.0.join("heroes.csv");
+    std::fs::write(
+        &path,
+        "name,role,level,favourite,blacklisted,stadium
+ tracer ,Damage,5,true,false,false
+  Ana,Support,2,false,false,false
+",
+    )
+    .unwrap();
+    let mut heroes = roster().heroes();
+
+    assert_eq!(import_csv(&path, &mut heroes).unwrap(), 2);
+
+    // The padded name updates the existing hero instead of adding another
+    assert_eq!(heroes.iter().count(), 4);
+    assert!(heroes.iter().any(|hero| hero.name == "Ana"));
+    let tracer = heroes.find_mut("Tracer").unwrap();
+    assert_eq!(tracer.level, 5);
+    assert!(tracer.favourite);
+}
+
+#[test]
+fn backups_in_the_same_second_are_kept() {
+    let dir = TempDir::new("same-second");
+    let path = dir.0.join("heroes.yaml");
+    std::fs::write(&path, "first").unwrap();
+    let first = create_timestamped_backup(&path).unwrap();
+    std::fs::write(&path, "second").unwrap();
+    let second = create_timestamped_backup(&path).unwrap();
+
+    assert_ne!(first, second);
+    assert_eq!(std::fs::read_to_string(&first).unwrap(), "first");
+    assert_eq!(std::fs::read_to_string(&second).unwrap(), "second");
+    assert_eq!(list_backups(&path).len(), 2);
+}
+
+#[test]
+fn restoring_right_after_a_save_keeps_both_backups() {
+    let dir = TempDir::new("save-restore");
+    let path = dir.0.join("heroes.yaml");
+    let old = dir.0.join("heroes.yaml.20000101-120000.bak");
+    std::fs::write(&old, "old").unwrap();
+    std::fs::write(&path, "saved").unwrap();
+
+    rotate_backups(&path).unwrap();
+    restore_backup(&path, &old).unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+    let saved = list_backups(&path)
+        .iter()
+        .filter(|backup| std::fs::read_to_string(backup).unwrap() == "saved")
+        .count();
+    assert_eq!(saved, 2);
+}